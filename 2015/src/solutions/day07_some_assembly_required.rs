@@ -1,128 +1,46 @@
-use std::{collections::HashMap, str::FromStr};
+use lazy_static::lazy_static;
+use utils::circuit::Circuit;
+use utils::expr::{Associativity, Expr, Grammar};
 
-#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
-enum Signal {
-    Value(u32),
-    Wire((char, char)),
+lazy_static! {
+    /// Bitwise logic gates that can be connected to wires
+    static ref GATES: Grammar<u16> = Grammar::new()
+        .binary("AND", 1, Associativity::Left, |a, b| a & b)
+        .binary("OR", 1, Associativity::Left, |a, b| a | b)
+        .binary("LSHIFT", 1, Associativity::Left, |a, b| a << b)
+        .binary("RSHIFT", 1, Associativity::Left, |a, b| a >> b)
+        .prefix("NOT", 2, |a| !a);
 }
 
-impl FromStr for Signal {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if let Ok(value) = s.parse() {
-            Ok(Self::Value(value))
-        } else {
-            Ok(Self::new_wire(s))
-        }
-    }
-}
-
-#[derive(Debug, Copy, Clone)]
-enum Gate {
-    Direct(Signal),
-    Not(Signal),
-    And(Signal, Signal),
-    Or(Signal, Signal),
-    LShift(Signal, Signal),
-    RShift(Signal, Signal),
-}
-
-/// Parses line into an output signal and gate tuple
-fn read_line(s: &str) -> (Signal, Gate) {
-    let parts = s.split_ascii_whitespace().collect::<Vec<_>>();
-    match parts[1] {
-        "AND" => (
-            parts[4].parse().unwrap(),
-            Gate::And(parts[0].parse().unwrap(), parts[2].parse().unwrap()),
-        ),
-        "OR" => (
-            parts[4].parse().unwrap(),
-            Gate::Or(parts[0].parse().unwrap(), parts[2].parse().unwrap()),
-        ),
-        "LSHIFT" => (
-            parts[4].parse().unwrap(),
-            Gate::LShift(parts[0].parse().unwrap(), parts[2].parse().unwrap()),
-        ),
-        "RSHIFT" => (
-            parts[4].parse().unwrap(),
-            Gate::RShift(parts[0].parse().unwrap(), parts[2].parse().unwrap()),
-        ),
-        "->" => (
-            parts[2].parse().unwrap(),
-            Gate::Direct(parts[0].parse().unwrap()),
-        ),
-        _ => {
-            assert_eq!(parts[0], "NOT");
-            (
-                parts[3].parse().unwrap(),
-                Gate::Not(parts[1].parse().unwrap()),
-            )
-        }
-    }
-}
-
-impl Signal {
-    /// Creates a new `Signal::Wire`
-    fn new_wire(wire: &str) -> Self {
-        // Wire names are 1/2 chars
-        let mut chars = wire.chars();
-        let name = (chars.next().unwrap(), chars.next().unwrap_or(' '));
-        Self::Wire(name)
-    }
-
-    /// Recursively computes the value of the `Signal`
-    /// Updates `circuit` with the value to avoid re-evaluating a `Signal` many times
-    fn value(&self, circuit: &mut HashMap<Self, Gate>) -> u32 {
-        match self {
-            Self::Value(value) => *value,
-            Self::Wire(_) => {
-                // Compute the value of the wire by recursively evaluating all wires/gates connected to it
-                let value = circuit.get(self).unwrap().clone().output(circuit);
-                // Replace this circuit with a direct value to avoid later re-evaluation
-                circuit.insert(*self, Gate::Direct(Self::Value(value)));
-                value
-            }
-        }
-    }
-}
-
-impl Gate {
-    /// Computes the output of a Gate
-    fn output(&self, circuit: &mut HashMap<Signal, Self>) -> u32 {
-        match self {
-            Self::Direct(a) => a.value(circuit),
-            Self::Not(a) => !a.value(circuit),
-            Self::And(a, b) => a.value(circuit) & b.value(circuit),
-            Self::Or(a, b) => a.value(circuit) | b.value(circuit),
-            Self::LShift(a, b) => a.value(circuit) << b.value(circuit),
-            Self::RShift(a, b) => a.value(circuit) >> b.value(circuit),
-        }
-    }
+/// Parses line into an output wire and the expression that drives it
+fn read_line(s: &str) -> (String, Expr<u16>) {
+    let (expr, wire) = s.split_once(" -> ").unwrap();
+    (wire.to_owned(), GATES.parse(expr).unwrap())
 }
 
 #[aoc_generator(day7)]
-fn gen(input: &str) -> HashMap<Signal, Gate> {
+fn gen(input: &str) -> Circuit<u16> {
     input.lines().map(read_line).collect()
 }
 
 #[aoc(day7, part1)]
-fn part1(input: &HashMap<Signal, Gate>) -> u32 {
+fn part1(input: &Circuit<u16>) -> u16 {
     // Compute the value of a
-    let a = input.get(&Signal::new_wire("a")).unwrap();
-    a.output(&mut input.clone())
+    let mut circuit = input.clone();
+    circuit.evaluate(&GATES).unwrap();
+    *circuit.signal("a").unwrap()
 }
 
 #[aoc(day7, part2)]
-fn part2(input: &HashMap<Signal, Gate>) -> u32 {
+fn part2(input: &Circuit<u16>) -> u16 {
     // Compute the value of a
-    let a = input.get(&Signal::new_wire("a")).unwrap();
-    let value = a.output(&mut input.clone());
-    // Reset the circuit with b overridden
     let mut circuit = input.clone();
-    circuit.insert(Signal::new_wire("b"), Gate::Direct(Signal::Value(value)));
-    // Compute new value of a
-    a.output(&mut circuit)
+    circuit.evaluate(&GATES).unwrap();
+    let a = *circuit.signal("a").unwrap();
+    // Override b and re-evaluate only the wires that depend on it
+    circuit.set("b", Expr::Value(a));
+    circuit.evaluate(&GATES).unwrap();
+    *circuit.signal("a").unwrap()
 }
 
 #[cfg(test)]
@@ -1,3 +1,4 @@
+use utils::automaton::{Automaton, Edge};
 use utils::grid::{Pos, VecGrid};
 
 #[aoc_generator(day18)]
@@ -5,38 +6,27 @@ fn gen(input: &str) -> VecGrid<char> {
     input.parse().unwrap()
 }
 
+/// Lights stay on with 2 or 3 neighbours that are on and turn on with exactly 3
+fn rule(light: char, neighbours: &[char]) -> char {
+    match (light, neighbours.iter().filter(|&&n| n == '#').count()) {
+        ('#', 2 | 3) | ('.', 3) => '#',
+        _ => '.',
+    }
+}
+
 // Simulates a set number of steps on the grid of lights
 fn simulate(input: &VecGrid<char>, steps: usize, force_corners_on: bool) -> usize {
+    let automaton = Automaton::new(Edge::Fixed('.'), rule);
     let mut current = input.clone();
     for _ in 0..steps {
-        let mut next = current.clone();
-        for (pos, value) in &current {
-            // For all positions, count neighbours that are currently on
-            let on_neighbours = current
-                .neighbours8_ex(pos)
-                .filter(|(_, x)| x == &Some('#'))
-                .count();
-            // Then update state as needed
-            if *value == '#' {
-                if !(2..=3).contains(&on_neighbours) {
-                    // Turn off
-                    next.insert(pos, '.');
-                }
-            } else {
-                if 3 == on_neighbours {
-                    // Turn on
-                    next.insert(pos, '#');
-                }
-            }
-        }
+        current = automaton.step(&current);
         if force_corners_on {
             // Force corners to stay on
-            next.insert(Pos::new(0, 0), '#');
-            next.insert(Pos::new(0, current.width() - 1), '#');
-            next.insert(Pos::new(current.height() - 1, 0), '#');
-            next.insert(Pos::new(current.height() - 1, current.width() - 1), '#');
+            let (width, height) = (current.width() - 1, current.height() - 1);
+            for corner in [(0, 0), (width, 0), (0, height), (width, height)] {
+                current.insert(Pos::from(corner), '#');
+            }
         }
-        std::mem::swap(&mut next, &mut current);
     }
     current.values().filter(|&&x| x == '#').count()
 }
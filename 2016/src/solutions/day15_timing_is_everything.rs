@@ -1,3 +1,5 @@
+use utils::math::crt;
+
 #[derive(Clone)]
 struct Disc {
     id: usize,
@@ -14,12 +16,19 @@ impl Disc {
         }
     }
 
-    /// Returns true if the slot on the disc will be lined up (at position 0) at a given time
-    const fn lined_up(&self, time: usize) -> bool {
-        (self.id + self.start + time) % self.positions == 0
+    /// Returns the (remainder, modulus) that the time must satisfy for the slot on the disc
+    /// to be lined up (at position 0) i.e. `id + start + time = 0 mod positions`
+    const fn congruence(&self) -> (i64, i64) {
+        (-((self.id + self.start) as i64), self.positions as i64)
     }
 }
 
+/// Finds the first time that all discs will be lined up
+fn first_lined_up(discs: &[Disc]) -> usize {
+    let congruences = discs.iter().map(Disc::congruence).collect::<Vec<_>>();
+    crt(&congruences).unwrap().0 as usize
+}
+
 #[aoc_generator(day15)]
 fn gen(input: &str) -> Vec<Disc> {
     input
@@ -38,18 +47,14 @@ fn gen(input: &str) -> Vec<Disc> {
 
 #[aoc(day15, part1)]
 fn part1(input: &[Disc]) -> usize {
-    (0..)
-        .find(|time| input.iter().all(|disc| disc.lined_up(*time)))
-        .unwrap()
+    first_lined_up(input)
 }
 
 #[aoc(day15, part2)]
 fn part2(input: &[Disc]) -> usize {
     let mut discs = input.to_vec();
     discs.push(Disc::new(input.len() + 1, 11, 0));
-    (0..)
-        .find(|time| discs.iter().all(|disc| disc.lined_up(*time)))
-        .unwrap()
+    first_lined_up(&discs)
 }
 
 #[cfg(test)]
@@ -1,37 +1,39 @@
 use itertools::Itertools;
+use std::ops::RangeInclusive;
+use utils::interval::RangeSet;
 
-/// Returns the allowed IPs based on the supplied ranges
-fn allowed(input: &str) -> impl Iterator<Item = usize> + '_ {
-    let mut current = 0;
-    input
+/// All possible IPs
+const IPS: RangeInclusive<usize> = 0..=u32::MAX as usize;
+
+/// Returns the ranges of allowed IPs based on the supplied blocked ranges
+fn allowed(input: &str) -> Vec<RangeInclusive<usize>> {
+    // Read in the ranges, merging any that overlap
+    let blocked = input
         .lines()
         .map(|line| {
-            // Read in the ranges
-            line.split('-')
+            let (min, max) = line
+                .split('-')
                 .map(|x| x.parse().unwrap())
                 .collect_tuple::<(usize, usize)>()
-                .unwrap()
-        })
-        .sorted_by(|a, b| a.0.cmp(&b.0)) // Sort from lowest to highest
-        .flat_map(move |(min, max)| {
-            // Allow all IPs between current and next min. If current >= min then this range will be empty
-            let allowed = current..min;
-            if max >= current {
-                // Advance the current value to be after the end of the range
-                current = max + 1;
-            }
-            allowed
+                .unwrap();
+            min..=max
         })
+        .collect::<RangeSet<_>>();
+    // Allow all IPs that are not in a blocked range
+    blocked.gaps(IPS).collect()
 }
 
 #[aoc(day20, part1)]
 fn part1(input: &str) -> usize {
-    allowed(input).next().unwrap()
+    *allowed(input)[0].start()
 }
 
 #[aoc(day20, part2)]
 fn part2(input: &str) -> usize {
-    allowed(input).count()
+    allowed(input)
+        .iter()
+        .map(|range| range.end() - range.start() + 1)
+        .sum()
 }
 
 #[cfg(test)]
@@ -1,4 +1,5 @@
 use crate::chronal_device::Instr;
+use utils::math::divisors;
 
 #[aoc_generator(day19)]
 fn gen(input: &str) -> (usize, Vec<Instr>) {
@@ -52,7 +53,7 @@ fn part2(input: &(usize, Vec<Instr>)) -> Option<usize> {
         if registers[*ip_register] == 33 {
             let target = registers[instr.c];
             // Quickly sum up all numbers that are a factor of the target number
-            return Some((1..=target).filter(|&x| target % x == 0).sum());
+            return Some(divisors(target).iter().sum());
         }
         // Advance the IP
         registers[*ip_register] += 1;
@@ -0,0 +1,306 @@
+//! Intcode assembler
+//!
+//! Assembles programs written in the same syntax as the listings produced by `disasm` so that
+//! test programs don't need to be encoded by hand. Each line holds an optional address (which
+//! is ignored), any number of labels and then an instruction or `.data` directive:
+//!
+//! ```text
+//! ; Comments start with a semicolon
+//!         in [x]
+//! loop:   out [x]
+//!         add [x], #-1, [x]
+//!         jt [x], #loop
+//!         hlt
+//! x:      .data 0
+//! ```
+//!
+//! Operands are written as `[x]` (position), `#x` (immediate) or `rb+x`/`rb-x` (relative),
+//! where `x` is either a number or a label.
+
+use super::intcode::Op;
+use std::collections::HashMap;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum AsmError {
+    #[error("line {line}: unknown mnemonic '{mnemonic}'")]
+    UnknownMnemonic { line: usize, mnemonic: String },
+    #[error("line {line}: invalid operand '{operand}'")]
+    InvalidOperand { line: usize, operand: String },
+    #[error("line {line}: '{mnemonic}' takes {expected} operand(s) but {found} were given")]
+    OperandCount {
+        line: usize,
+        mnemonic: String,
+        expected: usize,
+        found: usize,
+    },
+    #[error("line {line}: '{mnemonic}' can't write its result to an immediate")]
+    ImmediateWrite { line: usize, mnemonic: String },
+    #[error("line {line}: label '{label}' is already defined")]
+    DuplicateLabel { line: usize, label: String },
+    #[error("line {line}: label '{label}' is not defined")]
+    UndefinedLabel { line: usize, label: String },
+}
+
+/// Number or a reference to the address of a label
+enum Value {
+    Number(isize),
+    Label(String),
+}
+
+/// Value that is written into the program once all labels are known
+struct Pending {
+    addr: usize,
+    line: usize,
+    value: Value,
+}
+
+/// Assembles a program into a `Vec<isize>` that can be run using `Intcode::new`
+pub fn assemble(source: &str) -> Result<Vec<isize>, AsmError> {
+    let mut program = Vec::new();
+    let mut labels = HashMap::new();
+    let mut pending = Vec::new();
+    for (idx, text) in source.lines().enumerate() {
+        let line = idx + 1;
+        // Remove any comments and the address from listings
+        let mut text = text.split(';').next().unwrap().trim();
+        if let Some((addr, rest)) = text.split_once(':') {
+            if !addr.is_empty() && addr.chars().all(|c| c.is_ascii_digit()) {
+                text = rest.trim();
+            }
+        }
+        // Labels refer to the address of whatever comes next
+        while let Some((label, rest)) = text.split_once(':') {
+            if !is_label(label) {
+                break;
+            }
+            if labels.insert(label.to_owned(), program.len()).is_some() {
+                return Err(AsmError::DuplicateLabel {
+                    line,
+                    label: label.to_owned(),
+                });
+            }
+            text = rest.trim();
+        }
+        if text.is_empty() {
+            continue;
+        }
+        let (mnemonic, operands) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+        let operands = operands
+            .split(',')
+            .map(str::trim)
+            .filter(|operand| !operand.is_empty())
+            .collect::<Vec<_>>();
+        if mnemonic == ".data" {
+            for operand in operands {
+                let value = parse_value(operand).ok_or_else(|| invalid(line, operand))?;
+                pending.push(Pending {
+                    addr: program.len(),
+                    line,
+                    value,
+                });
+                program.push(0);
+            }
+            continue;
+        }
+        let (opcode, op) = (0..100)
+            .find_map(|opcode| {
+                Op::from_opcode(opcode)
+                    .filter(|op| op.mnemonic() == mnemonic)
+                    .map(|op| (opcode, op))
+            })
+            .ok_or_else(|| AsmError::UnknownMnemonic {
+                line,
+                mnemonic: mnemonic.to_owned(),
+            })?;
+        if operands.len() != op.params() {
+            return Err(AsmError::OperandCount {
+                line,
+                mnemonic: mnemonic.to_owned(),
+                expected: op.params(),
+                found: operands.len(),
+            });
+        }
+        // Encode the parameter modes into the opcode
+        let mut encoded = opcode as isize;
+        let mut values = Vec::with_capacity(operands.len());
+        for (param, operand) in operands.iter().enumerate() {
+            let (mode, value) = parse_operand(operand).ok_or_else(|| invalid(line, operand))?;
+            if mode == 1 && op.writes() && param + 1 == op.params() {
+                return Err(AsmError::ImmediateWrite {
+                    line,
+                    mnemonic: mnemonic.to_owned(),
+                });
+            }
+            encoded += mode * 10_isize.pow(param as u32 + 2);
+            values.push(value);
+        }
+        program.push(encoded);
+        for value in values {
+            pending.push(Pending {
+                addr: program.len(),
+                line,
+                value,
+            });
+            program.push(0);
+        }
+    }
+    // Fill in all of the operands and data now that the address of every label is known
+    for Pending { addr, line, value } in pending {
+        program[addr] = match value {
+            Value::Number(number) => number,
+            Value::Label(label) => match labels.get(&label) {
+                Some(&target) => target as isize,
+                None => return Err(AsmError::UndefinedLabel { line, label }),
+            },
+        };
+    }
+    Ok(program)
+}
+
+/// Parses an operand into (mode, value)
+fn parse_operand(operand: &str) -> Option<(isize, Value)> {
+    if let Some(inner) = operand.strip_prefix('[') {
+        Some((0, parse_value(inner.strip_suffix(']')?)?))
+    } else if let Some(value) = operand.strip_prefix('#') {
+        Some((1, parse_value(value)?))
+    } else if let Some(offset) = operand.strip_prefix("rb") {
+        let offset = offset.trim_start();
+        let value = if offset.is_empty() {
+            Value::Number(0)
+        } else if let Some(offset) = offset.strip_prefix('+') {
+            parse_value(offset.trim_start())?
+        } else {
+            Value::Number(offset.parse().ok()?)
+        };
+        Some((2, value))
+    } else {
+        None
+    }
+}
+
+/// Parses a number or label
+fn parse_value(value: &str) -> Option<Value> {
+    if let Ok(number) = value.parse() {
+        Some(Value::Number(number))
+    } else if is_label(value) {
+        Some(Value::Label(value.to_owned()))
+    } else {
+        None
+    }
+}
+
+/// Checks whether `s` is a valid label name
+fn is_label(s: &str) -> bool {
+    s.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Creates the error for an operand that couldn't be parsed
+fn invalid(line: usize, operand: &str) -> AsmError {
+    AsmError::InvalidOperand {
+        line,
+        operand: operand.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::disasm::listing;
+    use crate::intcode::Intcode;
+
+    #[test]
+    fn test_assemble_and_run() {
+        let program = assemble(
+            "
+            ; Output x + y
+                    add [x], [y], [result]
+                    out [result]
+                    hlt
+            x:      .data 5
+            y:      .data 6
+            result: .data 0
+        ",
+        )
+        .unwrap();
+        assert_eq!(program, vec![1, 7, 8, 9, 4, 9, 99, 5, 6, 0]);
+        let mut computer = Intcode::new(program);
+        computer.run().unwrap();
+        assert_eq!(computer.outputs().pop_front(), Some(11));
+    }
+
+    #[test]
+    fn test_loop() {
+        // Count down from the input to 1
+        let program = assemble(
+            "
+                    in [x]
+            loop:   out [x]
+                    add [x], #-1, [x]
+                    jt [x], #loop
+                    hlt
+            x:      .data 0
+        ",
+        )
+        .unwrap();
+        let mut computer = Intcode::new(program);
+        computer.inputs().push_back(3);
+        computer.run().unwrap();
+        assert_eq!(
+            computer.outputs().iter().copied().collect::<Vec<_>>(),
+            vec![3, 2, 1]
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let programs = [
+            vec![
+                109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
+            ],
+            vec![3, 12, 6, 12, 15, 1, 13, 14, 13, 4, 13, 99, -1, 0, 1, 9],
+            vec![21101, 7, 0, 0, 1105, 1, 9, 99, 42, 104, 1, 2106, 0, 0],
+            vec![109, -2, 203, 7, 99, 0],
+        ];
+        for program in programs {
+            assert_eq!(assemble(&listing(&program)), Ok(program));
+        }
+        // Labels can share names with the relative base and spaces are allowed in offsets
+        assert_eq!(
+            assemble("jt #1, #end\nend: rb: rb_1: hlt\narb #rb\nout rb + 5"),
+            Ok(vec![1105, 1, 3, 99, 109, 3, 204, 5])
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            assemble("hlt\njmp #1"),
+            Err(AsmError::UnknownMnemonic {
+                line: 2,
+                mnemonic: "jmp".to_owned()
+            })
+        );
+        assert_eq!(
+            assemble("out 5").unwrap_err().to_string(),
+            "line 1: invalid operand '5'"
+        );
+        assert_eq!(
+            assemble("add [1], [2]").unwrap_err().to_string(),
+            "line 1: 'add' takes 3 operand(s) but 2 were given"
+        );
+        assert_eq!(
+            assemble("in #4").unwrap_err().to_string(),
+            "line 1: 'in' can't write its result to an immediate"
+        );
+        assert_eq!(
+            assemble("a: hlt\na: hlt").unwrap_err().to_string(),
+            "line 2: label 'a' is already defined"
+        );
+        assert_eq!(
+            assemble("jt #1, #b").unwrap_err().to_string(),
+            "line 1: label 'b' is not defined"
+        );
+    }
+}
@@ -0,0 +1,61 @@
+use std::convert::TryFrom;
+use std::fmt::{Debug, Display};
+use std::hash::Hash;
+
+/// Integer types that can be stored in the memory of an `Intcode` computer
+///
+/// `isize` is used by default, but programs that probe large values can be run using `i128`
+pub trait Cell: Copy + Default + Eq + Ord + Hash + Debug + Display {
+    /// The value 0 (false)
+    const ZERO: Self;
+    /// The value 1 (true)
+    const ONE: Self;
+
+    /// Adds two values, returning None if the result overflows
+    fn checked_add(self, other: Self) -> Option<Self>;
+
+    /// Multiplies two values, returning None if the result overflows
+    fn checked_mul(self, other: Self) -> Option<Self>;
+
+    /// Converts an `isize` (e.g. an ASCII input) into a value
+    fn from_isize(value: isize) -> Self;
+
+    /// Converts the value into an `isize` (e.g. to use it as an address) if it fits
+    fn to_isize(self) -> Option<isize>;
+
+    /// Converts the value into an `i128` (which every cell fits in)
+    fn to_i128(self) -> i128;
+}
+
+macro_rules! impl_cell {
+    ($($t:ty),*) => {
+        $(
+            impl Cell for $t {
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+
+                fn checked_add(self, other: Self) -> Option<Self> {
+                    <$t>::checked_add(self, other)
+                }
+
+                fn checked_mul(self, other: Self) -> Option<Self> {
+                    <$t>::checked_mul(self, other)
+                }
+
+                fn from_isize(value: isize) -> Self {
+                    value as Self
+                }
+
+                fn to_isize(self) -> Option<isize> {
+                    isize::try_from(self).ok()
+                }
+
+                fn to_i128(self) -> i128 {
+                    self as i128
+                }
+            }
+        )*
+    };
+}
+
+impl_cell!(isize, i64, i128);
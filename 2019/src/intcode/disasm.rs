@@ -0,0 +1,309 @@
+//! Intcode disassembler
+//!
+//! Turns a program into a listing of addressed instructions such as `0004: mul [4], #3, [4]`.
+//! Code is found by following the control flow from address 0, with everything that is never
+//! reached (or doesn't decode as a valid instruction) shown as data.
+
+use super::intcode::Op;
+use super::Intcode;
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fmt;
+
+/// Maximum number of values shown on a single line of data
+const DATA_PER_LINE: usize = 8;
+
+/// How the value of an operand is interpreted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// The value is the address of the parameter (`[x]`)
+    Position,
+    /// The value is the parameter itself (`#x`)
+    Immediate,
+    /// The value is an offset from the relative base to the address of the parameter (`rb+x`)
+    Relative,
+}
+
+/// Parameter of an instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Operand {
+    pub mode: Mode,
+    pub value: isize,
+}
+
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.mode {
+            Mode::Position => write!(f, "[{}]", self.value),
+            Mode::Immediate => write!(f, "#{}", self.value),
+            Mode::Relative if self.value < 0 => write!(f, "rb{}", self.value),
+            Mode::Relative => write!(f, "rb+{}", self.value),
+        }
+    }
+}
+
+/// Decoded contents of part of a program
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Statement {
+    Instruction {
+        mnemonic: &'static str,
+        operands: Vec<Operand>,
+    },
+    Data(Vec<isize>),
+}
+
+impl fmt::Display for Statement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (name, values) = match self {
+            Self::Instruction { mnemonic, operands } => (
+                *mnemonic,
+                operands.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            ),
+            Self::Data(values) => (".data", values.iter().map(ToString::to_string).collect()),
+        };
+        if values.is_empty() {
+            write!(f, "{}", name)
+        } else {
+            write!(f, "{} {}", name, values.join(", "))
+        }
+    }
+}
+
+/// Line of a listing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Line {
+    pub addr: usize,
+    pub statement: Statement,
+}
+
+impl fmt::Display for Line {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}: {}", self.addr, self.statement)
+    }
+}
+
+/// Instruction decoded from a program
+struct Decoded {
+    op: Op,
+    operands: Vec<Operand>,
+}
+
+impl Decoded {
+    /// Gets the number of values used by the instruction
+    fn len(&self) -> usize {
+        1 + self.operands.len()
+    }
+
+    /// Gets the value of an operand if it is known without running the program
+    fn constant(&self, param: usize) -> Option<isize> {
+        let operand = self.operands[param];
+        (operand.mode == Mode::Immediate).then_some(operand.value)
+    }
+}
+
+/// Decodes the instruction at `addr` (if it is a valid instruction)
+fn decode(program: &[isize], addr: usize) -> Option<Decoded> {
+    let encoded = *program.get(addr)?;
+    if encoded < 0 {
+        return None;
+    }
+    let op = Op::from_opcode((encoded % 100) as usize)?;
+    let mut modes = encoded / 100;
+    let mut operands = Vec::with_capacity(op.params());
+    for param in 0..op.params() {
+        let mode = match modes % 10 {
+            0 => Mode::Position,
+            1 => Mode::Immediate,
+            2 => Mode::Relative,
+            _ => return None,
+        };
+        modes /= 10;
+        operands.push(Operand {
+            mode,
+            value: *program.get(addr + 1 + param)?,
+        });
+    }
+    // Reject modes for parameters that don't exist and results that can't be written
+    if modes != 0 || (op.writes() && operands.last()?.mode == Mode::Immediate) {
+        return None;
+    }
+    Some(Decoded { op, operands })
+}
+
+/// Finds the instructions that can be reached by following the control flow from address 0
+///
+/// Jumps are only followed when their target is an immediate. Constants written relative to
+/// the relative base are also followed as they are typically return addresses pushed onto
+/// the stack before calling a function.
+fn reachable(program: &[isize]) -> BTreeMap<usize, Decoded> {
+    let mut code = BTreeMap::new();
+    let mut queue = vec![0];
+    while let Some(addr) = queue.pop() {
+        if code.contains_key(&addr) {
+            continue;
+        }
+        let instr = match decode(program, addr) {
+            Some(instr) => instr,
+            None => continue,
+        };
+        let next = addr + instr.len();
+        let targets = match instr.op {
+            Op::Halt => vec![],
+            Op::JumpIfTrue | Op::JumpIfFalse => {
+                let mut targets = vec![instr.constant(1)];
+                // Only continue on to the next instruction if the jump might not be taken
+                let always = match instr.constant(0) {
+                    Some(condition) => (condition != 0) == (instr.op == Op::JumpIfTrue),
+                    None => false,
+                };
+                if !always {
+                    targets.push(Some(next as isize));
+                }
+                targets
+            }
+            Op::Add | Op::Multiply if instr.operands[2].mode == Mode::Relative => {
+                let result = match (instr.constant(0), instr.constant(1)) {
+                    (Some(a), Some(b)) if instr.op == Op::Add => a.checked_add(b),
+                    (Some(a), Some(b)) => a.checked_mul(b),
+                    _ => None,
+                };
+                vec![result, Some(next as isize)]
+            }
+            _ => vec![Some(next as isize)],
+        };
+        queue.extend(
+            targets
+                .into_iter()
+                .flatten()
+                .filter_map(|t| usize::try_from(t).ok()),
+        );
+        code.insert(addr, instr);
+    }
+    code
+}
+
+/// Disassembles a program into lines of instructions and data
+pub fn disassemble(program: &[isize]) -> Vec<Line> {
+    let code = reachable(program);
+    let mut lines = Vec::new();
+    let mut addr = 0;
+    while addr < program.len() {
+        if let Some(instr) = code.get(&addr) {
+            lines.push(Line {
+                addr,
+                statement: Statement::Instruction {
+                    mnemonic: instr.op.mnemonic(),
+                    operands: instr.operands.clone(),
+                },
+            });
+            addr += instr.len();
+        } else {
+            // Group data up until the next instruction
+            let end = code
+                .range(addr..)
+                .next()
+                .map_or(program.len(), |(next, _)| *next)
+                .min(addr + DATA_PER_LINE);
+            lines.push(Line {
+                addr,
+                statement: Statement::Data(program[addr..end].to_vec()),
+            });
+            addr = end;
+        }
+    }
+    lines
+}
+
+/// Disassembles a program into a listing with one line per instruction (or group of data)
+pub fn listing(program: &[isize]) -> String {
+    disassemble(program)
+        .iter()
+        .map(|line| format!("{}\n", line))
+        .collect()
+}
+
+impl Intcode {
+    /// Disassembles the current contents of memory
+    pub fn disassemble(&self) -> Vec<Line> {
+        let mut lines = disassemble(self.memory());
+        // Ignore any unused memory at the end
+        while let Some(Line {
+            statement: Statement::Data(values),
+            ..
+        }) = lines.last()
+        {
+            if values.iter().any(|&value| value != 0) {
+                break;
+            }
+            lines.pop();
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_operands() {
+        assert_eq!(
+            listing(&[1002, 4, 3, 4, 33]),
+            "0000: mul [4], #3, [4]\n0004: .data 33\n"
+        );
+        assert_eq!(
+            listing(&[109, 19, 204, -34, 21201, -1, 7, 5, 99]),
+            "0000: arb #19\n0002: out rb-34\n0004: add rb-1, #7, rb+5\n0008: hlt\n"
+        );
+    }
+
+    #[test]
+    fn test_data_detection() {
+        // Data after a halt
+        assert_eq!(
+            listing(&[1, 1, 1, 4, 99, 5, 6, 0, 99]),
+            "0000: add [1], [1], [4]\n0004: hlt\n0005: .data 5, 6, 0, 99\n"
+        );
+        // Data skipped by an unconditional jump
+        assert_eq!(
+            listing(&[1105, 1, 7, 5, 6, 0, 0, 104, 42, 99]),
+            "0000: jt #1, #7\n0003: .data 5, 6, 0, 0\n0007: out #42\n0009: hlt\n"
+        );
+        // Invalid instructions are data even if reached
+        assert_eq!(
+            listing(&[1, 0, 0, 3, 55, 1, 2]),
+            "0000: add [0], [0], [3]\n0004: .data 55, 1, 2\n"
+        );
+        assert_eq!(
+            listing(&[1, 0, 0, 3, 301, 1, 2, 3, 4, 5, 6, 7, 8]),
+            "0000: add [0], [0], [3]\n0004: .data 301, 1, 2, 3, 4, 5, 6, 7\n0012: .data 8\n"
+        );
+        assert_eq!(listing(&[11101, 1, 2, 3]), "0000: .data 11101, 1, 2, 3\n");
+    }
+
+    #[test]
+    fn test_function_call() {
+        // Push a return address (7) and call the function at 9 which returns with jf #0, rb+0
+        let mut computer = Intcode::new_with(
+            &vec![21101, 7, 0, 0, 1105, 1, 9, 99, 42, 104, 1, 2106, 0, 0],
+            100,
+        );
+        assert_eq!(
+            computer
+                .disassemble()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            vec![
+                "0000: add #7, #0, rb+0",
+                "0004: jt #1, #9",
+                "0007: hlt",
+                "0008: .data 42",
+                "0009: out #1",
+                "0011: jf #0, rb+0",
+            ]
+        );
+        computer.run().unwrap();
+        assert_eq!(computer.outputs().pop_front(), Some(1));
+    }
+}
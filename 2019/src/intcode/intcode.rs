@@ -1,5 +1,8 @@
-use digits_iterator::DigitsExtension;
-use std::collections::VecDeque;
+use super::Cell;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::convert::TryFrom;
+use std::fmt;
+use thiserror::Error;
 
 /// AOC 2019 Intcode implementation
 ///
@@ -11,18 +14,122 @@ use std::collections::VecDeque;
 /// // Create a new instance using the program
 /// let mut computer = Intcode::new(program);
 /// // Run the loaded program
-/// computer.run();
+/// computer.run().unwrap();
 /// // Get and check the result (this program stores it in address 0)
 /// let result = computer.get_mem(0);
 /// assert_eq!(result, 30);
 /// ```
+///
+/// Memory cells are `isize` by default, `Intcode::<i128>::parse` can be used to run programs
+/// that need larger values. Arithmetic is checked so that overflows are reported rather than
+/// silently wrapping.
+///
+/// Memory grows as the program uses it so it never needs to be sized up front. Addresses
+/// close to the end of memory extend it, while those far beyond it are stored sparsely so that
+/// a single write to a very high address doesn't allocate everything below it.
+///
+/// Programs can also be debugged by executing one instruction at a time using `step`, or by
+/// setting breakpoints and watchpoints and running until one is hit using `resume`.
 #[derive(Clone)]
-pub struct Intcode {
-    mem: Vec<isize>,
+pub struct Intcode<T: Cell = isize> {
+    mem: Vec<T>,
+    sparse: HashMap<usize, T>,
     ip: usize,
-    inputs: VecDeque<isize>,
-    outputs: VecDeque<isize>,
+    inputs: VecDeque<T>,
+    outputs: VecDeque<T>,
     relative_base: isize,
+    breakpoints: HashSet<usize>,
+    watchpoints: HashSet<usize>,
+    trace: Option<Vec<Executed<T>>>,
+    counts: [usize; Op::ALL.len()],
+    halted: bool,
+}
+
+/// Furthest beyond the end of memory that a write will extend it rather than being stored sparsely
+const MAX_GROWTH: usize = 1024 * 1024;
+
+/// Errors that stop a program from running
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum IntcodeError {
+    #[error("arithmetic overflow at ip {ip}")]
+    Overflow { ip: usize },
+    #[error("invalid opcode {opcode} at ip {ip}")]
+    InvalidOpcode { ip: usize, opcode: i128 },
+    #[error("invalid mode {mode} for parameter {param} at ip {ip}")]
+    InvalidMode { ip: usize, param: usize, mode: u8 },
+    #[error("write in immediate mode at ip {ip}")]
+    ImmediateWrite { ip: usize },
+    #[error("negative address {addr} at ip {ip}")]
+    NegativeAddress { ip: usize, addr: isize },
+}
+
+/// State of a program after it has stopped running
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunState<T: Cell = isize> {
+    /// The program has finished
+    Halted,
+    /// The program is blocked on an input
+    NeedsInput,
+    /// The program has output a value
+    Output(T),
+}
+
+/// Reason that `Intcode::resume` stopped running the program
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pause<T: Cell = isize> {
+    /// The program has finished
+    Halted,
+    /// The program is blocked on an input
+    NeedsInput,
+    /// The next instruction to be executed is at a breakpoint
+    Breakpoint { ip: usize },
+    /// An instruction has written to a watched address
+    Watchpoint { executed: Executed<T> },
+}
+
+/// Record of an instruction that has been executed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Executed<T: Cell = isize> {
+    /// Address of the instruction
+    pub ip: usize,
+    /// Opcode of the instruction (including parameter modes)
+    pub opcode: T,
+    /// Value that was written to memory or output (if any)
+    pub result: Option<T>,
+    /// Address that the result was written to (if any)
+    pub write: Option<usize>,
+    op: Op,
+    params: [T; 3],
+}
+
+impl<T: Cell> Executed<T> {
+    /// Gets the mnemonic of the instruction (as used by the disassembler)
+    pub const fn mnemonic(&self) -> &'static str {
+        self.op.mnemonic()
+    }
+
+    /// Gets the raw values of the operands (before the parameter modes are applied)
+    pub fn operands(&self) -> &[T] {
+        &self.params[..self.op.params()]
+    }
+}
+
+impl<T: Cell> fmt::Display for Executed<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}: {}", self.ip, self.mnemonic())?;
+        let operands = self
+            .operands()
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        if !operands.is_empty() {
+            write!(f, " {}", operands.join(", "))?;
+        }
+        if let Some(result) = self.result {
+            write!(f, " => {}", result)?;
+        }
+        Ok(())
+    }
 }
 
 /// Result of executing an instruction
@@ -30,11 +137,12 @@ enum Result {
     SetIP(usize),
     InputRequired,
     Exit,
+    Error(IntcodeError),
 }
 
 /// Type of operation and associated (static) configuration
-#[derive(Debug, PartialEq)]
-enum Op {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Op {
     Add,
     Multiply,
     Input,
@@ -48,10 +156,24 @@ enum Op {
 }
 
 impl Op {
-    /// Creates a new op from an opcode
-    fn new(opcode: usize) -> Self {
+    /// All ops (in opcode order)
+    const ALL: [Self; 10] = [
+        Self::Add,
+        Self::Multiply,
+        Self::Input,
+        Self::Output,
+        Self::JumpIfTrue,
+        Self::JumpIfFalse,
+        Self::LessThan,
+        Self::Equals,
+        Self::AdjustRelativeBase,
+        Self::Halt,
+    ];
+
+    /// Creates a new op from an opcode (if it is valid)
+    pub(crate) const fn from_opcode(opcode: usize) -> Option<Self> {
         use Op::*;
-        match opcode {
+        Some(match opcode {
             1 => Add,
             2 => Multiply,
             3 => Input,
@@ -62,12 +184,35 @@ impl Op {
             8 => Equals,
             9 => AdjustRelativeBase,
             99 => Halt,
-            _ => panic!("Unexpected opcode {}", opcode),
+            _ => return None,
+        })
+    }
+
+    /// Gets the short name used for the op in listings
+    pub(crate) const fn mnemonic(&self) -> &'static str {
+        use Op::*;
+        match self {
+            Add => "add",
+            Multiply => "mul",
+            Input => "in",
+            Output => "out",
+            JumpIfTrue => "jt",
+            JumpIfFalse => "jf",
+            LessThan => "lt",
+            Equals => "eq",
+            AdjustRelativeBase => "arb",
+            Halt => "hlt",
         }
     }
 
+    /// Checks whether the op writes its result to the address given by its last parameter
+    pub(crate) const fn writes(&self) -> bool {
+        use Op::*;
+        matches!(self, Add | Multiply | Input | LessThan | Equals)
+    }
+
     /// Gets the instruction implementation
-    fn instr_impl(&self) -> fn(&mut Intcode, param_addrs: &[usize]) -> Option<Result> {
+    fn instr_impl<T: Cell>(&self) -> fn(&mut Intcode<T>, param_addrs: &[usize]) -> Option<Result> {
         use Op::*;
         match self {
             Add => Intcode::instr_1_add,
@@ -84,7 +229,7 @@ impl Op {
     }
 
     /// Gets the number of parameters used
-    const fn params(&self) -> usize {
+    pub(crate) const fn params(&self) -> usize {
         use Op::*;
         match self {
             Add | Multiply | LessThan | Equals => 3,
@@ -104,85 +249,259 @@ struct Instr {
     param_modes: [u8; 3],
 }
 
+impl Instr {
+    /// Decodes the instruction at `ip`
+    fn decode(ip: usize, encoded: isize) -> std::result::Result<Self, IntcodeError> {
+        // Extract the opcode and param modes
+        let op = usize::try_from(encoded % 100)
+            .ok()
+            .and_then(Op::from_opcode)
+            .ok_or(IntcodeError::InvalidOpcode {
+                ip,
+                opcode: encoded as i128,
+            })?;
+        let mut param_modes = [0, 0, 0];
+        let mut modes = encoded / 100;
+        for (param, param_mode) in param_modes.iter_mut().enumerate().take(op.params()) {
+            let mode = (modes % 10) as u8;
+            if mode > 2 {
+                return Err(IntcodeError::InvalidMode { ip, param, mode });
+            }
+            *param_mode = mode;
+            modes /= 10;
+        }
+        // Results can't be written to an immediate value
+        if op.writes() && param_modes[op.params() - 1] == 1 {
+            return Err(IntcodeError::ImmediateWrite { ip });
+        }
+        Ok(Self { op, param_modes })
+    }
+}
+
 impl Intcode {
     /// Initialises a new Intcode computer with the supplied program
     pub fn new(program: Vec<isize>) -> Self {
-        Self {
-            mem: program,
-            ip: 0,
-            inputs: VecDeque::new(),
-            outputs: VecDeque::new(),
-            relative_base: 0,
-        }
+        Self::load(program)
     }
 
     /// Initialises a new Intcode computer with the supplied program with the specified memory size
     /// (anything beyond the length of the program will be initialised to 0)
+    ///
+    /// Memory will still grow if needed, but allocating it up front avoids growing it while running
     pub fn new_with(program: &Vec<isize>, memory_size: usize) -> Self {
-        // Create a new instance with memory of the required size set to 0
-        let mut result = Self::new(vec![0; memory_size]);
-        // Then load our program into it
-        for (id, val) in program.iter().enumerate() {
-            result.mem[id] = *val;
+        // Pad the program with 0s up to the required size (a longer program is kept as is)
+        let mut mem = program.clone();
+        if mem.len() < memory_size {
+            mem.resize(memory_size, 0);
         }
-        result
+        Self::new(mem)
     }
 
     /// Initialises a new Intcode computer from a comma separated string of integers
     pub fn from(program: &str) -> Self {
-        Self {
-            mem: program
-                .split(',')
-                .map(|i| i.parse::<isize>().unwrap())
-                .collect(),
-            ip: 0,
-            inputs: VecDeque::new(),
-            outputs: VecDeque::new(),
-            relative_base: 0,
-        }
+        Self::parse(program)
     }
 
     /// Initialises a new Intcode computer from a comma separated string of integers
     /// with the specified memory size (anything beyond the length of the program will be initialised to 0)
+    ///
+    /// Memory will still grow if needed, but allocating it up front avoids growing it while running
     pub fn from_with(program: &str, memory_size: usize) -> Self {
-        // Create a new instance with memory of the required size set to 0
-        let mut result = Self::new(vec![0; memory_size]);
-        // Then load our program into it
-        for (id, val) in program
+        let program = program
             .split(',')
             .map(|i| i.parse::<isize>().unwrap())
-            .enumerate()
-        {
-            result.mem[id] = val;
+            .collect();
+        Self::new_with(&program, memory_size)
+    }
+}
+
+impl<T: Cell> Intcode<T> {
+    /// Initialises a new Intcode computer with the supplied program of any cell type
+    pub fn load(program: Vec<T>) -> Self {
+        Self {
+            mem: program,
+            sparse: HashMap::new(),
+            ip: 0,
+            inputs: VecDeque::new(),
+            outputs: VecDeque::new(),
+            relative_base: 0,
+            breakpoints: HashSet::new(),
+            watchpoints: HashSet::new(),
+            trace: None,
+            counts: [0; Op::ALL.len()],
+            halted: false,
         }
-        result
+    }
+
+    /// Initialises a new Intcode computer of any cell type from a comma separated string of integers
+    pub fn parse(program: &str) -> Self
+    where
+        T: std::str::FromStr,
+        T::Err: std::fmt::Debug,
+    {
+        Self::load(
+            program
+                .split(',')
+                .map(|i| i.trim().parse::<T>().unwrap())
+                .collect(),
+        )
     }
 
     /// Sets the value at the supplied memory address
-    pub fn set_mem(&mut self, addr: usize, value: isize) {
-        self.mem[addr] = value;
+    pub fn set_mem(&mut self, addr: usize, value: T) {
+        self.write(addr, value);
+    }
+
+    /// Gets the value at the supplied memory address (which is 0 if it has never been set)
+    pub fn get_mem(&self, addr: usize) -> T {
+        self.read(addr)
     }
 
-    /// Gets the value at the supplied memory address
-    pub fn get_mem(&self, addr: usize) -> isize {
-        self.mem[addr]
+    /// Gets the contents of memory up to the highest address that isn't stored sparsely
+    pub fn memory(&self) -> &[T] {
+        &self.mem
     }
 
-    /// Runs the loaded program until completion (returns true)
-    /// or it is blocked on an input (returns false)
+    /// Runs the loaded program until completion (returns `Halted`)
+    /// or it is blocked on an input (returns `NeedsInput`)
     /// (In which case an input should be supplied and run should be called again to resume)
-    pub fn run(&mut self) -> bool {
+    ///
+    /// Never returns `Output`, any values that are output are added to the output queue
+    /// (use `run_until_output` to stop at each one instead). Once the program has halted
+    /// running it again returns `Halted` without executing anything.
+    ///
+    /// # Errors
+    ///
+    /// If an instruction is invalid, uses a negative address or overflows
+    pub fn run(&mut self) -> std::result::Result<RunState<T>, IntcodeError> {
+        loop {
+            match self.execute()? {
+                None => return Ok(self.stopped()),
+                Some(executed) if executed.op == Op::Halt => return Ok(RunState::Halted),
+                Some(_) => {}
+            }
+        }
+    }
+
+    /// Runs the loaded program until it outputs a value (returns `Output` instead of adding
+    /// it to the output queue), completes (returns `Halted`) or is blocked on an input
+    /// (returns `NeedsInput`)
+    ///
+    /// # Errors
+    ///
+    /// If an instruction is invalid, uses a negative address or overflows
+    pub fn run_until_output(&mut self) -> std::result::Result<RunState<T>, IntcodeError> {
+        loop {
+            match self.execute()? {
+                None => return Ok(self.stopped()),
+                Some(executed) if executed.op == Op::Halt => return Ok(RunState::Halted),
+                Some(executed) if executed.op == Op::Output => {
+                    self.outputs.pop_back();
+                    return Ok(RunState::Output(executed.result.unwrap()));
+                }
+                Some(_) => {}
+            }
+        }
+    }
+
+    /// Executes the next instruction, returning it (or None if it is blocked on an input
+    /// or has already halted, which can be told apart using `halted`)
+    ///
+    /// # Errors
+    ///
+    /// If the instruction fails (e.g. an arithmetic overflow)
+    pub fn step(&mut self) -> std::result::Result<Option<Executed<T>>, IntcodeError> {
+        self.execute()
+    }
+
+    /// Runs the loaded program until completion, it is blocked on an input, the next
+    /// instruction is at a breakpoint or an instruction writes to a watched address
+    ///
+    /// The first instruction is always executed so that calling `resume` again after
+    /// stopping at a breakpoint continues on from it
+    ///
+    /// # Errors
+    ///
+    /// If an instruction fails (e.g. an arithmetic overflow)
+    pub fn resume(&mut self) -> std::result::Result<Pause<T>, IntcodeError> {
         loop {
-            match self.execute(self.ip) {
-                Result::SetIP(ip) => self.ip = ip,
-                Result::InputRequired => return false,
-                Result::Exit => return true,
+            match self.execute()? {
+                None if self.halted => return Ok(Pause::Halted),
+                None => return Ok(Pause::NeedsInput),
+                Some(executed) if executed.op == Op::Halt => return Ok(Pause::Halted),
+                Some(executed) => {
+                    if executed
+                        .write
+                        .map_or(false, |addr| self.watchpoints.contains(&addr))
+                    {
+                        return Ok(Pause::Watchpoint { executed });
+                    }
+                }
+            }
+            if self.breakpoints.contains(&self.ip) {
+                return Ok(Pause::Breakpoint { ip: self.ip });
             }
         }
     }
 
+    /// Gets the address of the next instruction to be executed
+    pub const fn ip(&self) -> usize {
+        self.ip
+    }
+
+    /// Checks whether the program has executed a halt instruction
+    pub const fn halted(&self) -> bool {
+        self.halted
+    }
+
+    /// Pauses `resume` before executing the instruction at `ip`
+    pub fn add_breakpoint(&mut self, ip: usize) {
+        self.breakpoints.insert(ip);
+    }
+
+    /// Removes a breakpoint (returning false if there wasn't one at `ip`)
+    pub fn remove_breakpoint(&mut self, ip: usize) -> bool {
+        self.breakpoints.remove(&ip)
+    }
+
+    /// Pauses `resume` after executing an instruction that writes to `addr`
+    pub fn add_watchpoint(&mut self, addr: usize) {
+        self.watchpoints.insert(addr);
+    }
+
+    /// Removes a watchpoint (returning false if there wasn't one at `addr`)
+    pub fn remove_watchpoint(&mut self, addr: usize) -> bool {
+        self.watchpoints.remove(&addr)
+    }
+
+    /// Starts (or stops) recording every instruction that is executed
+    pub fn set_tracing(&mut self, enabled: bool) {
+        self.trace = enabled.then(Vec::new);
+    }
+
+    /// Takes all of the instructions that have been recorded since tracing was enabled
+    /// (or `take_trace` was last called)
+    pub fn take_trace(&mut self) -> Vec<Executed<T>> {
+        self.trace.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    /// Gets the total number of instructions that have been executed
+    pub fn instructions_executed(&self) -> usize {
+        self.counts.iter().sum()
+    }
+
+    /// Gets the number of times each type of instruction has been executed as (mnemonic, count)
+    pub fn instruction_counts(&self) -> Vec<(&'static str, usize)> {
+        Op::ALL
+            .iter()
+            .zip(self.counts)
+            .filter(|(_, count)| *count > 0)
+            .map(|(op, count)| (op.mnemonic(), count))
+            .collect()
+    }
+
     /// Gets the input queue
-    pub fn inputs(&mut self) -> &mut VecDeque<isize> {
+    pub fn inputs(&mut self) -> &mut VecDeque<T> {
         &mut self.inputs
     }
 
@@ -191,118 +510,188 @@ impl Intcode {
     /// Inputs the line as ASCII chars and terminates with a new line
     pub fn inputln(&mut self, line: &str) {
         for c in line.chars() {
-            self.inputs.push_back(c as isize);
+            self.inputs.push_back(T::from_isize(c as isize));
         }
-        self.inputs.push_back(10); // New line
+        self.inputs.push_back(T::from_isize(10)); // New line
     }
 
     /// Prints outputs as ASCII chars
     pub fn print_outputs_as_ascii(&mut self) {
         self.outputs()
             .drain(0..) // Consume all outputs and treat as chars
-            .for_each(|c| print!("{}", c as u8 as char));
+            .for_each(|c| print!("{}", c.to_isize().unwrap() as u8 as char));
     }
 
     /// Returns outputs as ASCII chars
     pub fn outputs_as_ascii(&mut self) -> String {
         self.outputs()
             .drain(0..) // Consume all outputs and treat as chars
-            .map(|c| c as u8 as char)
+            .map(|c| c.to_isize().unwrap() as u8 as char)
             .collect()
     }
 
     /// Gets the output queue
-    pub fn outputs(&mut self) -> &mut VecDeque<isize> {
+    pub fn outputs(&mut self) -> &mut VecDeque<T> {
         &mut self.outputs
     }
 
-    /// Executes a single instruction at the specified IP
-    fn execute(&mut self, ip: usize) -> Result {
+    /// Gets the state of a program that couldn't execute the next instruction
+    fn stopped(&self) -> RunState<T> {
+        if self.halted {
+            RunState::Halted
+        } else {
+            RunState::NeedsInput
+        }
+    }
+
+    /// Executes the instruction at the IP, returning it (or None if it is blocked on an input
+    /// or has already halted)
+    fn execute(&mut self) -> std::result::Result<Option<Executed<T>>, IntcodeError> {
+        if self.halted {
+            return Ok(None);
+        }
+        let ip = self.ip;
+        let opcode = self.read(ip);
         // Decode the instruction
-        let instr = Self::decode(self.mem[ip]);
-        // Get the reference to the implementation
-        let instr_impl = instr.op.instr_impl();
+        let encoded = opcode.to_isize().ok_or(IntcodeError::InvalidOpcode {
+            ip,
+            opcode: opcode.to_i128(),
+        })?;
+        let instr = Instr::decode(ip, encoded)?;
+        let count = instr.op.params();
+        let mut params = [T::ZERO; 3];
+        for (param, value) in params.iter_mut().enumerate().take(count) {
+            *value = self.read(ip + 1 + param);
+        }
         // Get the parameters
-        let param_addrs = self.param_addrs(ip, instr.op.params(), &instr.param_modes);
+        let param_addrs = self.param_addrs(ip, count, &instr.param_modes)?;
+        let write = instr.op.writes().then(|| param_addrs[count - 1]);
         // Execute it
-        let result = instr_impl(self, &param_addrs);
-        if result.is_none() {
+        match instr.op.instr_impl()(self, &param_addrs) {
             // Simple instructions with no result
             // Just advance the instruction pointer by 1 + num params
-            return Result::SetIP(ip + 1 + instr.op.params());
+            None => self.ip = ip + 1 + count,
+            Some(Result::SetIP(next)) => self.ip = next,
+            Some(Result::InputRequired) => return Ok(None),
+            Some(Result::Exit) => self.halted = true,
+            Some(Result::Error(error)) => return Err(error),
         }
-        result.unwrap()
-    }
-
-    /// Decodes an instruction
-    fn decode(encoded: isize) -> Instr {
-        // Extract the opcode and param modes
-        let mut opcode = 0;
-        let mut param_modes = [0, 0, 0];
-        for (idx, digit) in encoded
-            .digits()
-            .collect::<Vec<_>>()
-            .iter()
-            .rev()
-            .enumerate()
-        {
-            match idx {
-                0 => opcode += digit,
-                1 => opcode += 10 * digit,
-                _ => param_modes[idx - 2] = *digit as u8,
-            }
+        let result = match instr.op {
+            Op::Output => self.outputs.back().copied(),
+            _ => write.map(|addr| self.read(addr)),
+        };
+        let executed = Executed {
+            ip,
+            opcode,
+            result,
+            write,
+            op: instr.op,
+            params,
+        };
+        self.counts[instr.op as usize] += 1;
+        if let Some(trace) = &mut self.trace {
+            trace.push(executed);
         }
-        // Lookup the opcode
-        let op = Op::new(opcode as usize);
-        Instr { op, param_modes }
+        Ok(Some(executed))
     }
 
     /// Gets the address of parameters for an instruction taking into account the different parameter modes
-    fn param_addrs(&self, ip: usize, count: usize, modes: &[u8]) -> Vec<usize> {
+    fn param_addrs(
+        &self,
+        ip: usize,
+        count: usize,
+        modes: &[u8],
+    ) -> std::result::Result<Vec<usize>, IntcodeError> {
         (0..count)
             .map(|param| {
                 let addr = ip + 1 + param;
                 match modes[param] {
-                    0 => {
-                        // Position mode - return the value at the address
-                        self.mem[addr] as usize
-                    }
-                    1 => {
-                        // Immediate mode - return the address directly
-                        addr
-                    }
-                    2 => {
-                        // Relative mode - return the relative base + the value at the address
-                        (self.relative_base + self.mem[addr]) as usize
-                    }
-                    _ => {
-                        panic!("Unexpected parameter mode {}", modes[param])
-                    }
+                    // Position mode - return the value at the address
+                    0 => self.address(self.read(addr), 0),
+                    // Immediate mode - return the address directly
+                    1 => Ok(addr),
+                    // Relative mode - return the relative base + the value at the address
+                    _ => self.address(self.read(addr), self.relative_base),
                 }
             })
             .collect()
     }
 
+    /// Converts `value + offset` into an address
+    fn address(&self, value: T, offset: isize) -> std::result::Result<usize, IntcodeError> {
+        let ip = self.ip;
+        let addr = value
+            .to_isize()
+            .and_then(|value| value.checked_add(offset))
+            .ok_or(IntcodeError::Overflow { ip })?;
+        usize::try_from(addr).map_err(|_| IntcodeError::NegativeAddress { ip, addr })
+    }
+
+    /// Gets the value at `addr`
+    fn read(&self, addr: usize) -> T {
+        self.mem
+            .get(addr)
+            .or_else(|| self.sparse.get(&addr))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Sets the value at `addr`, growing memory if needed
+    fn write(&mut self, addr: usize, value: T) {
+        if addr < self.mem.len() {
+            self.mem[addr] = value;
+        } else if addr - self.mem.len() < MAX_GROWTH {
+            self.mem.resize(addr + 1, T::ZERO);
+            self.mem[addr] = value;
+            // Move anything that was stored sparsely into the newly grown memory
+            let mem = &mut self.mem;
+            self.sparse.retain(|&addr, value| {
+                mem.get_mut(addr).map_or(true, |cell| {
+                    *cell = *value;
+                    false
+                })
+            });
+        } else {
+            self.sparse.insert(addr, value);
+        }
+    }
+
     /// Gets the next available input
-    fn input(&mut self) -> Option<isize> {
+    fn input(&mut self) -> Option<T> {
         self.inputs.pop_front()
     }
 
     /// Outputs the supplied value
-    fn output(&mut self, value: isize) {
+    fn output(&mut self, value: T) {
         self.outputs.push_back(value);
     }
 
     /// Sets the 3rd parameter to the 1st plus the 2nd
     fn instr_1_add(&mut self, param_addrs: &[usize]) -> Option<Result> {
-        self.mem[param_addrs[2]] = self.mem[param_addrs[0]] + self.mem[param_addrs[1]];
-        None
+        match self
+            .read(param_addrs[0])
+            .checked_add(self.read(param_addrs[1]))
+        {
+            Some(value) => {
+                self.write(param_addrs[2], value);
+                None
+            }
+            None => Some(Result::Error(IntcodeError::Overflow { ip: self.ip })),
+        }
     }
 
     /// Sets the 3rd parameter to the 1st multiplied by the 2nd
     fn instr_2_multiply(&mut self, param_addrs: &[usize]) -> Option<Result> {
-        self.mem[param_addrs[2]] = self.mem[param_addrs[0]] * self.mem[param_addrs[1]];
-        None
+        match self
+            .read(param_addrs[0])
+            .checked_mul(self.read(param_addrs[1]))
+        {
+            Some(value) => {
+                self.write(param_addrs[2], value);
+                None
+            }
+            None => Some(Result::Error(IntcodeError::Overflow { ip: self.ip })),
+        }
     }
 
     /// Fetches an input and stores it in the 1st parameter
@@ -311,7 +700,7 @@ impl Intcode {
     fn instr_3_input(&mut self, param_addrs: &[usize]) -> Option<Result> {
         if let Some(value) = self.input() {
             // Input value is available, write it to memory
-            self.mem[param_addrs[0]] = value;
+            self.write(param_addrs[0], value);
             None
         } else {
             // No input available
@@ -323,14 +712,14 @@ impl Intcode {
 
     /// Outputs the 1st parameter
     fn instr_4_output(&mut self, param_addrs: &[usize]) -> Option<Result> {
-        self.output(self.mem[param_addrs[0]]);
+        self.output(self.read(param_addrs[0]));
         None
     }
 
     /// Sets the IP to the value of the 2nd parameter if the 1st is not-equal to 0
     fn instr_5_jump_if_true(&mut self, param_addrs: &[usize]) -> Option<Result> {
-        if self.mem[param_addrs[0]] != 0 {
-            Some(Result::SetIP(self.mem[param_addrs[1]] as usize))
+        if self.read(param_addrs[0]) != T::ZERO {
+            Some(self.jump(self.read(param_addrs[1])))
         } else {
             None
         }
@@ -338,37 +727,56 @@ impl Intcode {
 
     /// Sets the IP to the value of the 2nd parameter if the 1st is equal to 0
     fn instr_6_jump_if_false(&mut self, param_addrs: &[usize]) -> Option<Result> {
-        if self.mem[param_addrs[0]] == 0 {
-            Some(Result::SetIP(self.mem[param_addrs[1]] as usize))
+        if self.read(param_addrs[0]) == T::ZERO {
+            Some(self.jump(self.read(param_addrs[1])))
         } else {
             None
         }
     }
 
+    /// Gets the result of jumping to `target`
+    fn jump(&self, target: T) -> Result {
+        match self.address(target, 0) {
+            Ok(ip) => Result::SetIP(ip),
+            Err(error) => Result::Error(error),
+        }
+    }
+
     /// Sets the 3rd parameter to 1 if the 1st is less than the second, else sets to 0
     fn instr_7_less_than(&mut self, param_addrs: &[usize]) -> Option<Result> {
-        self.mem[param_addrs[2]] = if self.mem[param_addrs[0]] < self.mem[param_addrs[1]] {
-            1
+        let value = if self.read(param_addrs[0]) < self.read(param_addrs[1]) {
+            T::ONE
         } else {
-            0
+            T::ZERO
         };
+        self.write(param_addrs[2], value);
         None
     }
 
     /// Sets the 3rd parameter to 1 if the 1st and second are equal, else sets to 0
     fn instr_8_equals(&mut self, param_addrs: &[usize]) -> Option<Result> {
-        self.mem[param_addrs[2]] = if self.mem[param_addrs[0]] == self.mem[param_addrs[1]] {
-            1
+        let value = if self.read(param_addrs[0]) == self.read(param_addrs[1]) {
+            T::ONE
         } else {
-            0
+            T::ZERO
         };
+        self.write(param_addrs[2], value);
         None
     }
 
     /// Adjusts the relative base by the amount in the 1st parameter
     fn instr_9_adjust_relative_base(&mut self, param_addrs: &[usize]) -> Option<Result> {
-        self.relative_base += self.mem[param_addrs[0]];
-        None
+        match self
+            .read(param_addrs[0])
+            .to_isize()
+            .and_then(|adjustment| self.relative_base.checked_add(adjustment))
+        {
+            Some(relative_base) => {
+                self.relative_base = relative_base;
+                None
+            }
+            None => Some(Result::Error(IntcodeError::Overflow { ip: self.ip })),
+        }
     }
 
     /// Unconditionally causes the program to exit
@@ -380,18 +788,19 @@ impl Intcode {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::intcode::asm::assemble;
 
     #[test]
     fn test_instr_1_add() {
         let mut computer = Intcode::new([1, 3, 3, 3].to_vec());
-        computer.execute(0);
+        computer.execute().unwrap();
         assert_eq!(computer.get_mem(3), 6);
     }
 
     #[test]
     fn test_instr_2_multi() {
         let mut computer = Intcode::new([2, 3, 3, 3].to_vec());
-        computer.execute(0);
+        computer.execute().unwrap();
         assert_eq!(computer.get_mem(3), 9);
     }
 
@@ -402,9 +811,8 @@ mod tests {
         // Stores the input in the location specified by 7 - 2 = 5
         let mut computer = Intcode::new([109, -2, 203, 7, 99, 0].to_vec());
         computer.inputs().push_back(22);
-        computer.run();
+        computer.run().unwrap();
         assert_eq!(computer.relative_base, -2);
-        println!("{:?}", computer.mem);
         assert_eq!(computer.get_mem(5), 22);
     }
 
@@ -412,7 +820,7 @@ mod tests {
     fn test_run_basic_day2_part1_1() {
         // Tests a sample program from day 2 part 1
         let mut computer = Intcode::new([1, 9, 10, 3, 2, 3, 11, 0, 99, 30, 40, 50].to_vec());
-        computer.run();
+        computer.run().unwrap();
         assert_eq!(computer.get_mem(0), 3500);
         assert_eq!(computer.get_mem(3), 70);
     }
@@ -421,27 +829,27 @@ mod tests {
     fn test_run_basic_day2_part1_2() {
         // Tests a sample program from day 2 part 1
         let mut computer = Intcode::new([1, 1, 1, 4, 99, 5, 6, 0, 99].to_vec());
-        computer.run();
+        computer.run().unwrap();
         assert_eq!(computer.get_mem(0), 30);
         assert_eq!(computer.get_mem(4), 2);
     }
 
     #[test]
     fn test_decode() {
-        assert_eq!(Intcode::decode(2).param_modes, [0, 0, 0]);
-        assert_eq!(Intcode::decode(99).param_modes, [0, 0, 0]);
-        assert_eq!(Intcode::decode(1002).param_modes, [0, 1, 0]);
-        assert_eq!(Intcode::decode(1002).op, Op::Multiply);
-        assert_eq!(Intcode::decode(1202).param_modes, [2, 1, 0]);
+        assert_eq!(Instr::decode(0, 2).unwrap().param_modes, [0, 0, 0]);
+        assert_eq!(Instr::decode(0, 99).unwrap().param_modes, [0, 0, 0]);
+        assert_eq!(Instr::decode(0, 1002).unwrap().param_modes, [0, 1, 0]);
+        assert_eq!(Instr::decode(0, 1002).unwrap().op, Op::Multiply);
+        assert_eq!(Instr::decode(0, 1202).unwrap().param_modes, [2, 1, 0]);
     }
 
     #[test]
     fn test_params() {
         let computer = Intcode::new([1002, 4, 3, 4, 33].to_vec());
-        let instr = Intcode::decode(1002);
+        let instr = Instr::decode(0, 1002).unwrap();
         assert_eq!(
             computer.param_addrs(0, instr.op.params(), &instr.param_modes),
-            [4, 2, 4].to_vec()
+            Ok([4, 2, 4].to_vec())
         );
     }
 
@@ -451,11 +859,11 @@ mod tests {
         let program = [3, 12, 6, 12, 15, 1, 13, 14, 13, 4, 13, 99, -1, 0, 1, 9].to_vec();
         let mut computer = Intcode::new(program.clone());
         computer.inputs().push_back(0);
-        computer.run();
+        computer.run().unwrap();
         assert_eq!(*computer.outputs().iter().last().unwrap(), 0);
         let mut computer = Intcode::new(program);
         computer.inputs().push_back(72);
-        computer.run();
+        computer.run().unwrap();
         assert_eq!(*computer.outputs().iter().last().unwrap(), 1);
     }
 
@@ -466,7 +874,7 @@ mod tests {
         ]
         .to_vec();
         let mut computer = Intcode::new_with(&program, 1024);
-        computer.run();
+        computer.run().unwrap();
         assert_eq!(
             computer.outputs().iter().copied().collect::<Vec<_>>(),
             program
@@ -477,7 +885,207 @@ mod tests {
     fn test_advanced_day_9_self_large_numbers() {
         let program = [104, 1125899906842624, 99].to_vec();
         let mut computer = Intcode::new_with(&program, 1024);
-        computer.run();
+        computer.run().unwrap();
         assert_eq!(computer.outputs().pop_front().unwrap(), 1125899906842624);
     }
+
+    #[test]
+    fn test_overflow() {
+        // Multiplies a large number by itself and then outputs it
+        let program = "1002,7,1125899906842624,7,4,7,99,1125899906842624";
+        let mut computer = Intcode::from(program);
+        assert_eq!(computer.run(), Err(IntcodeError::Overflow { ip: 0 }));
+        let mut computer = Intcode::from("1001,5,1,5,99,9223372036854775807");
+        assert_eq!(
+            computer.run().unwrap_err().to_string(),
+            "arithmetic overflow at ip 0"
+        );
+        // Using i128 cells gives the correct result
+        let mut computer = Intcode::<i128>::parse(program);
+        assert_eq!(computer.run(), Ok(RunState::Halted));
+        assert_eq!(
+            computer.outputs().pop_front(),
+            Some(1125899906842624 * 1125899906842624)
+        );
+    }
+
+    /// Outputs the input and then counts down to 1
+    const COUNTDOWN: &str = "
+                in [x]
+        loop:   out [x]
+                add [x], #-1, [x]
+                jt [x], #loop
+                hlt
+        x:      .data 0
+    ";
+
+    #[test]
+    fn test_step() {
+        let mut computer = Intcode::new(assemble(COUNTDOWN).unwrap());
+        assert_eq!(computer.step(), Ok(None));
+        computer.inputs().push_back(2);
+        let executed = computer.step().unwrap().unwrap();
+        assert_eq!(
+            (executed.ip, executed.mnemonic(), executed.operands()),
+            (0, "in", &[12][..])
+        );
+        assert_eq!((executed.result, executed.write), (Some(2), Some(12)));
+        assert_eq!(
+            computer.step().unwrap().unwrap().to_string(),
+            "0002: out 12 => 2"
+        );
+        assert_eq!(
+            computer.step().unwrap().unwrap().to_string(),
+            "0004: add 12, -1, 12 => 1"
+        );
+        assert_eq!(computer.ip(), 8);
+    }
+
+    #[test]
+    fn test_breakpoints_and_watchpoints() {
+        let mut computer = Intcode::new(assemble(COUNTDOWN).unwrap());
+        computer.inputs().push_back(3);
+        computer.add_breakpoint(2);
+        assert_eq!(computer.resume(), Ok(Pause::Breakpoint { ip: 2 }));
+        assert!(computer.outputs().is_empty());
+        // Resuming continues on from the breakpoint until it is hit again
+        assert_eq!(computer.resume(), Ok(Pause::Breakpoint { ip: 2 }));
+        assert_eq!(computer.outputs().pop_front(), Some(3));
+        assert!(computer.remove_breakpoint(2));
+        computer.add_watchpoint(12);
+        match computer.resume() {
+            Ok(Pause::Watchpoint { executed }) => {
+                assert_eq!((executed.ip, executed.result), (4, Some(1)));
+            }
+            other => panic!("Unexpected pause {:?}", other),
+        }
+        assert!(computer.remove_watchpoint(12));
+        assert!(!computer.remove_watchpoint(12));
+        assert_eq!(computer.resume(), Ok(Pause::Halted));
+        assert_eq!(
+            computer.outputs().iter().copied().collect::<Vec<_>>(),
+            vec![2, 1]
+        );
+    }
+
+    #[test]
+    fn test_trace_and_counts() {
+        let mut computer = Intcode::new(assemble(COUNTDOWN).unwrap());
+        computer.set_tracing(true);
+        computer.inputs().push_back(2);
+        assert_eq!(computer.run(), Ok(RunState::Halted));
+        let trace = computer.take_trace();
+        assert_eq!(
+            trace.iter().map(Executed::mnemonic).collect::<Vec<_>>(),
+            vec!["in", "out", "add", "jt", "out", "add", "jt", "hlt"]
+        );
+        assert_eq!(trace[3].to_string(), "0008: jt 12, 2");
+        assert!(computer.take_trace().is_empty());
+        assert_eq!(computer.instructions_executed(), 8);
+        assert_eq!(
+            computer.instruction_counts(),
+            vec![("add", 2), ("in", 1), ("out", 2), ("jt", 2), ("hlt", 1)]
+        );
+    }
+
+    #[test]
+    fn test_halted() {
+        // Once halted, running again doesn't re-execute (or re-count) the halt instruction
+        let mut computer = Intcode::new(assemble(COUNTDOWN).unwrap());
+        computer.inputs().push_back(1);
+        assert!(!computer.halted());
+        assert_eq!(computer.run(), Ok(RunState::Halted));
+        assert!(computer.halted());
+        assert_eq!(computer.run(), Ok(RunState::Halted));
+        assert_eq!(computer.run_until_output(), Ok(RunState::Halted));
+        assert_eq!(computer.resume(), Ok(Pause::Halted));
+        assert_eq!(computer.step(), Ok(None));
+        assert_eq!(computer.instruction_counts().last(), Some(&("hlt", 1)));
+        assert_eq!(computer.ip(), 11);
+    }
+
+    #[test]
+    fn test_run_until_output() {
+        let mut computer = Intcode::new(assemble(COUNTDOWN).unwrap());
+        assert_eq!(computer.run_until_output(), Ok(RunState::NeedsInput));
+        computer.inputs().push_back(2);
+        assert_eq!(computer.run_until_output(), Ok(RunState::Output(2)));
+        assert_eq!(computer.run_until_output(), Ok(RunState::Output(1)));
+        assert_eq!(computer.run_until_output(), Ok(RunState::Halted));
+        assert!(computer.outputs().is_empty());
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |program: Vec<isize>| Intcode::new(program).run().unwrap_err();
+        assert_eq!(
+            error(vec![1101, 1, 1, 5, 42, 0]),
+            IntcodeError::InvalidOpcode { ip: 4, opcode: 42 }
+        );
+        assert_eq!(error(vec![-1]).to_string(), "invalid opcode -1 at ip 0");
+        assert_eq!(
+            error(vec![104, 1, 304, 1]),
+            IntcodeError::InvalidMode {
+                ip: 2,
+                param: 0,
+                mode: 3
+            }
+        );
+        assert_eq!(
+            error(vec![11101, 1, 1, 0]).to_string(),
+            "write in immediate mode at ip 0"
+        );
+        assert_eq!(
+            error(vec![109, -5, 204, 1, 99]),
+            IntcodeError::NegativeAddress { ip: 2, addr: -4 }
+        );
+        assert_eq!(
+            error(vec![1105, 1, -3]).to_string(),
+            "negative address -3 at ip 0"
+        );
+    }
+
+    #[test]
+    fn test_memory_growth() {
+        let program = assemble(
+            "
+            add #5, #6, [100]
+            add [100], #1, [1000000000000]
+            out [1000000000000]
+            out [200]
+            hlt
+        ",
+        )
+        .unwrap();
+        let mut computer = Intcode::new(program.clone());
+        assert_eq!(computer.run(), Ok(RunState::Halted));
+        assert_eq!(
+            computer.outputs().drain(..).collect::<Vec<_>>(),
+            vec![12, 0]
+        );
+        assert_eq!(computer.get_mem(100), 11);
+        assert_eq!(computer.memory().len(), 101);
+        // Memory that was sized up front still grows and picks up anything stored sparsely
+        let mut computer = Intcode::new_with(&program, 50);
+        computer.set_mem(MAX_GROWTH + 60, 7);
+        computer.run().unwrap();
+        assert_eq!(computer.memory().len(), 101);
+        computer.set_mem(MAX_GROWTH, 1);
+        assert_eq!(computer.memory().len(), MAX_GROWTH + 1);
+        assert_eq!(computer.get_mem(MAX_GROWTH + 60), 7);
+        assert_eq!(computer.get_mem(1_000_000_000_000), 12);
+    }
+
+    #[test]
+    fn test_program_longer_than_memory_size() {
+        // Programs that don't fit in the requested memory size are loaded in full
+        let computer = Intcode::new_with(&vec![7; 10], 4);
+        assert_eq!(computer.memory(), &[7; 10]);
+        let mut computer = Intcode::from_with("1,0,0,0,104,42,99", 2);
+        assert_eq!(computer.run(), Ok(RunState::Halted));
+        assert_eq!(computer.outputs().pop_front(), Some(42));
+        assert_eq!(computer.get_mem(0), 2);
+        // While shorter ones are padded with 0s
+        assert_eq!(Intcode::new_with(&vec![1, 2], 4).memory(), &[1, 2, 0, 0]);
+    }
 }
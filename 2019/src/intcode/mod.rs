@@ -1,2 +1,6 @@
+pub mod asm;
+mod cell;
+pub mod disasm;
 pub mod intcode;
-pub use intcode::Intcode;
+pub use cell::Cell;
+pub use intcode::{Executed, Intcode, IntcodeError, Pause, RunState};
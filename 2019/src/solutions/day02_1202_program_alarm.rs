@@ -5,7 +5,7 @@ fn part1(input: &str) -> isize {
     let mut computer = Intcode::from(input);
     computer.set_mem(1, 12);
     computer.set_mem(2, 2);
-    computer.run();
+    computer.run().unwrap();
     computer.get_mem(0)
 }
 
@@ -17,7 +17,7 @@ fn part2(input: &str) -> isize {
             let mut computer = Intcode::from(input);
             computer.set_mem(1, noun);
             computer.set_mem(2, verb);
-            computer.run();
+            computer.run().unwrap();
             if computer.get_mem(0) == 19_690_720 {
                 return (100 * noun) + verb;
             }
@@ -4,7 +4,7 @@ use crate::intcode::Intcode;
 fn part1(input: &str) -> isize {
     let mut computer = Intcode::from(input);
     computer.inputs().push_back(1);
-    computer.run();
+    computer.run().unwrap();
     return *computer.outputs().iter().last().unwrap();
 }
 
@@ -12,6 +12,6 @@ fn part1(input: &str) -> isize {
 fn part2(input: &str) -> isize {
     let mut computer = Intcode::from(input);
     computer.inputs().push_back(5);
-    computer.run();
+    computer.run().unwrap();
     return *computer.outputs().iter().last().unwrap();
 }
@@ -1,4 +1,4 @@
-use crate::intcode::Intcode;
+use crate::intcode::{Intcode, RunState};
 use itertools::Itertools;
 
 fn run_chained_amps(program: &Vec<isize>, phases: &[&isize], feedback: bool) -> isize {
@@ -20,7 +20,7 @@ fn run_chained_amps(program: &Vec<isize>, phases: &[&isize], feedback: bool) ->
         for amp in &mut amps {
             // Give it the signal from the last amp (or 0 for the first)
             amp.inputs().push_back(signal);
-            if !amp.run() {
+            if amp.run().unwrap() == RunState::NeedsInput {
                 // Amp hasn't finished, must be waiting on an input
                 finished = false;
             }
@@ -1,4 +1,4 @@
-use crate::intcode::Intcode;
+use crate::intcode::{Intcode, RunState};
 use std::collections::HashMap;
 use utils::ocr::OcrString;
 
@@ -12,7 +12,7 @@ struct EmergencyHullPainingRobot {
 impl EmergencyHullPainingRobot {
     fn new(program: &str) -> Self {
         Self {
-            controller: Intcode::from_with(program, 1024 * 1024),
+            controller: Intcode::from(program),
             tiles: HashMap::new(),
             direction: 0,
             pos: (0, 0),
@@ -27,7 +27,7 @@ impl EmergencyHullPainingRobot {
             let tile = self.tiles.entry(self.pos).or_default();
             self.controller.inputs().push_back(*tile);
             // Run the controller, it will process the input and give us some outputs
-            if self.controller.run() {
+            if self.controller.run().unwrap() == RunState::Halted {
                 // Complete
                 return;
             }
@@ -1,4 +1,4 @@
-use crate::intcode::Intcode;
+use crate::intcode::{Intcode, RunState};
 use std::cmp::Ordering;
 
 struct BlocksGame {
@@ -10,7 +10,7 @@ struct BlocksGame {
 impl BlocksGame {
     fn from(program: &str) -> Self {
         Self {
-            computer: Intcode::from_with(program, 1024 * 1024),
+            computer: Intcode::from(program),
             tiles: vec![vec![0; 42]; 24], // Fix screen size to simplify logic,
             score: 0,
         }
@@ -45,7 +45,7 @@ impl BlocksGame {
         let mut paddle = 0;
         // Run the game loop until done
         loop {
-            let finished = self.computer.run();
+            let finished = self.computer.run().unwrap() == RunState::Halted;
             // Read the outputs (groups of 3s)
             for cmd in self.computer.outputs().iter().collect::<Vec<_>>().chunks(3) {
                 // Unpack the instruction
@@ -10,7 +10,7 @@ struct RepairDroid {
 impl RepairDroid {
     fn from(program: &str) -> Self {
         Self {
-            controller: Intcode::from_with(program, 1024 * 1024),
+            controller: Intcode::from(program),
             pos: (0, 0),
             tiles: HashMap::new(),
         }
@@ -89,7 +89,7 @@ impl RepairDroid {
             let next = Self::next(self.pos, direction);
             // Try to move to it
             self.controller.inputs().push_back(direction as isize);
-            self.controller.run();
+            self.controller.run().unwrap();
             // Check status to see if we were able to move
             let status = self.controller.outputs().pop_front().unwrap();
             self.tiles.insert(next, status);
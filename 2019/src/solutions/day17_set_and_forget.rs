@@ -30,14 +30,14 @@ struct ASCII {
 impl ASCII {
     pub fn from(program: &str) -> Self {
         Self {
-            controller: Intcode::from_with(program, 1024 * 1024),
+            controller: Intcode::from(program),
         }
     }
 
     /// Aligns the cameras and returns the sum of the alignment parameters
     pub fn align_cameras(&mut self) -> isize {
         // Run the program and get the grid
-        self.controller.run();
+        self.controller.run().unwrap();
         let grid = Self::decode_camera_output(&mut self.controller.outputs().iter());
         // Find cross over points and return the sum of each alignment param (x*y)
         return Self::find_crossover_points(&grid)
@@ -49,7 +49,7 @@ impl ASCII {
     pub fn walk_scaffolding(&mut self) -> isize {
         // Run a copy of the program and get the grid
         let mut tmp = self.controller.clone();
-        tmp.run();
+        tmp.run().unwrap();
         let grid = Self::decode_camera_output(&mut tmp.outputs().iter());
 
         let route = Self::build_route(&grid);
@@ -64,7 +64,7 @@ impl ASCII {
         // Tell it not to show a continuous feed
         self.controller.inputln("n");
         // Now run the robot
-        self.controller.run();
+        self.controller.run().unwrap();
 
         // Return collected dust as the final output value
         return self.controller.outputs().pop_back().unwrap();
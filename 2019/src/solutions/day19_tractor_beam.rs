@@ -4,10 +4,10 @@ use crate::intcode::Intcode;
 /// tractor beam is active there
 fn deploy(drone_program: &Vec<isize>, x: isize, y: isize) -> bool {
     // Deploy a drone to the supplied position
-    let mut drone = Intcode::new_with(drone_program, 512);
+    let mut drone = Intcode::new(drone_program.clone());
     drone.inputs().push_back(x);
     drone.inputs().push_back(y);
-    drone.run();
+    drone.run().unwrap();
     // Return status (0=nothing, 1=beam)
     drone.outputs().pop_front().unwrap() == 1
 }
@@ -18,7 +18,7 @@ impl SpringDroid {
             self.controller.inputln(line);
         }
         // Lets go!
-        self.controller.run();
+        self.controller.run().unwrap();
 
         if *self.controller.outputs().back().unwrap() < 256 {
             // Something has gone wrong (final value should be outside of ASCII range)
@@ -1,6 +1,6 @@
-use modinverse::modinverse;
 use std::convert::Infallible;
 use std::str::FromStr;
+use utils::math::Affine;
 
 #[derive(Clone, Copy, Debug)]
 enum Technique {
@@ -41,52 +41,44 @@ impl Technique {
         }
     }
 
-    /// Transforms the technique into a linear congruential function of form f(x) = ax + b mod m
-    /// Returns (a, b)
+    /// Transforms the technique into an affine transformation f(x) = ax + b mod `deck_size`
+    /// that gives the new index of the card at index x
     ///
     /// Based on <https://codeforces.com/blog/entry/72593>
-    fn lcf(&self) -> (i128, i128) {
+    const fn affine(&self, deck_size: i64) -> Affine {
         match self {
-            Self::DealIntoNewStack => (-1, -1),
-            Self::Cut(cards) => (1, -cards as i128),
-            Self::DealWithIncrement(increment) => (*increment as i128, 0),
+            Self::DealIntoNewStack => Affine::new(-1, -1, deck_size),
+            Self::Cut(cards) => Affine::new(1, -(*cards as i64), deck_size),
+            Self::DealWithIncrement(increment) => Affine::new(*increment as i64, 0, deck_size),
         }
     }
 }
 
-/// Composes the supplied techniques as lcfs into a single lcf given the supplied deck size
-fn compose_techniques_lcfs(techniques: &[Technique], deck_size: i128) -> (i128, i128) {
+/// Composes the techniques into a single affine transformation for the supplied deck size
+fn compose_techniques(techniques: &[Technique], deck_size: i64) -> Affine {
     techniques
         .iter()
-        .fold((1, 0), |acc, x| compose_lcfs(acc, x.lcf(), deck_size))
+        .fold(Affine::identity(deck_size), |acc, x| {
+            acc.then(&x.affine(deck_size))
+        })
 }
 
-/// Composes two lcf given deck size m
-///
-/// Turning f(x)=ax+b  mod m  and g(x)=cx+d  mod m into
-/// (a,b) ;(c,d)=(ac mod m,bc+d  mod m)
-/// Based on <https://codeforces.com/blog/entry/72593>
-fn compose_lcfs(f: (i128, i128), g: (i128, i128), m: i128) -> (i128, i128) {
-    let (a, b) = f;
-    let (c, d) = g;
-    ((a * c).rem_euclid(m), (b * c + d).rem_euclid(m))
+/// Gets the position that the card `card` ends up at after shuffling `times` times
+fn position_of_card(techniques: &[Technique], deck_size: i64, times: u64, card: i64) -> i64 {
+    compose_techniques(techniques, deck_size)
+        .pow(times)
+        .apply(card)
 }
 
-/// Composes a lcf k times based on deck size m using exponentiation by squaring
+/// Gets the card that ends up at `position` after shuffling `times` times
 ///
-/// Based on <https://codeforces.com/blog/entry/72593>
-fn pow_compose(f: (i128, i128), k: i128, m: i128) -> (i128, i128) {
-    let mut g = (1, 0);
-    let mut k = k;
-    let mut f = f;
-    while k > 0 {
-        if k % 2 != 0 {
-            g = compose_lcfs(g, f, m);
-        }
-        k /= 2;
-        f = compose_lcfs(f, f, m);
-    }
-    g
+/// Requires all increments to be coprime with the deck size (so that the shuffle can be undone)
+fn card_at_position(techniques: &[Technique], deck_size: i64, times: u64, position: i64) -> i64 {
+    compose_techniques(techniques, deck_size)
+        .pow(times)
+        .inverse()
+        .unwrap()
+        .apply(position)
 }
 
 /// Shuffles the deck of cards using the techniques
@@ -137,25 +129,16 @@ fn part1_single_card(input: &[Technique]) -> usize {
 
 /// Version of part 1 using modular arithmetic based on <https://codeforces.com/blog/entry/72593>
 #[aoc(day22, part1, modular_arithmetic)]
-fn part1_modular_arithmetic(input: &[Technique]) -> i128 {
-    // Compose the lcfs for all techniques based on our deck size into a single lcf
-    let deck_size = 10_007;
-    let (a, b) = compose_techniques_lcfs(input, deck_size);
-    // Calculate where card 2019 will end up by computing f(x)=ax+b  mod m
-    (a * 2019 + b).rem_euclid(deck_size)
+fn part1_modular_arithmetic(input: &[Technique]) -> i64 {
+    // Calculate where card 2019 will end up
+    position_of_card(input, 10_007, 1, 2019)
 }
 
 /// Part 2 using modular arithmetic based on <https://codeforces.com/blog/entry/72593>
 #[aoc(day22, part2)]
-fn part2_modular_arithmetic(input: &[Technique]) -> i128 {
-    // Compose the lcfs for all techniques based on our deck size into a single lcf
-    let deck_size = 119_315_717_514_047;
-    let (a, b) = compose_techniques_lcfs(input, deck_size);
-    // Compose them passes times into a new single lcf
-    let passes = 101_741_582_076_661;
-    let (a, b) = pow_compose((a, b), passes, deck_size);
-    // Calculate what cards ends up at 2020 by calculating the inverse
-    ((2020 - b) * modinverse(a, deck_size).unwrap()).rem_euclid(deck_size)
+fn part2_modular_arithmetic(input: &[Technique]) -> i64 {
+    // Calculate what card ends up at 2020 after shuffling many times by undoing the shuffle
+    card_at_position(input, 119_315_717_514_047, 101_741_582_076_661, 2020)
 }
 
 #[cfg(test)]
@@ -195,4 +178,26 @@ mod tests {
         let shuffled = shuffle(deck, &techniques);
         assert_eq!(shuffled, [3, 0, 7, 4, 1, 8, 5, 2, 9, 6]);
     }
+
+    #[test]
+    fn test_affine_matches_brute_force() {
+        let techniques = gen("deal into new stack\ncut -2\ndeal with increment 7\ncut 8\ncut -4\ndeal with increment 7\ncut 3\ndeal with increment 9\ndeal with increment 3\ncut -1");
+        for deck_size in [10, 11, 13, 10_007] {
+            let mut deck = (0..deck_size).collect::<Vec<_>>();
+            for times in 1..=5 {
+                deck = shuffle(deck, &techniques);
+                for (position, &card) in deck.iter().enumerate() {
+                    let (size, times) = (deck_size as i64, times as u64);
+                    assert_eq!(
+                        position_of_card(&techniques, size, times, card as i64),
+                        position as i64
+                    );
+                    assert_eq!(
+                        card_at_position(&techniques, size, times, position as i64),
+                        card as i64
+                    );
+                }
+            }
+        }
+    }
 }
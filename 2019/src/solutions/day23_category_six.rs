@@ -10,7 +10,7 @@ fn create_nics(input: &str, count: isize) -> Vec<Intcode> {
     (0..count)
         .map(|address| {
             // Create a new NIC and configure with an address
-            let mut nic = Intcode::new_with(&program, 10240);
+            let mut nic = Intcode::new(program.clone());
             nic.inputs().push_back(address);
             nic
         })
@@ -39,7 +39,7 @@ fn run(program: &str, stop_on_first_nat: bool) -> isize {
                 }
             }
             // Run until we need more input
-            nic.run();
+            nic.run().unwrap();
             // Store any outputs as packets in a queue to be received later
             if !nic.outputs().is_empty() {
                 for packet in nic.outputs().drain(0..).collect::<Vec<_>>().chunks(3) {
@@ -67,7 +67,7 @@ struct Droid {
 impl Droid {
     fn new(program: &str) -> Self {
         Self {
-            controller: Intcode::from_with(program, 1024 * 1024),
+            controller: Intcode::from(program),
         }
     }
 
@@ -99,7 +99,7 @@ impl Droid {
             command.push_str(item);
             self.controller.inputln(&command);
         }
-        self.controller.run();
+        self.controller.run().unwrap();
         self.controller.outputs().clear();
     }
 
@@ -112,7 +112,7 @@ impl Droid {
         // Find out what we are holding (check inventory)
         let mut inventory: Vec<String> = Vec::new();
         self.controller.inputln("inv");
-        self.controller.run();
+        self.controller.run().unwrap();
         for line in self.controller.outputs_as_ascii().lines().skip(1) {
             let mut chars = line.chars();
             if chars.next() == Some('-') {
@@ -129,7 +129,7 @@ impl Droid {
                 self.act_on_items(&combination, "take");
                 // Try to move through the door
                 self.controller.inputln(door);
-                self.controller.run();
+                self.controller.run().unwrap();
                 // Parse output
                 let output = self.controller.outputs_as_ascii();
                 // Interested in the last line
@@ -168,7 +168,7 @@ impl Droid {
         let mut security_checkpoint_route: Vec<String> = Vec::new();
         loop {
             // Process last input and get back info about the room
-            self.controller.run();
+            self.controller.run().unwrap();
             let output = self.controller.outputs_as_ascii();
             let room = output.parse::<Room>().unwrap();
             // Get/record which doors we still need to go through
@@ -183,7 +183,7 @@ impl Droid {
                             command.push_str(&item);
                             // Pick up the item and discard output message about what we just picked up
                             self.controller.inputln(&command);
-                            self.controller.run();
+                            self.controller.run().unwrap();
                             self.controller.outputs().clear();
                         }
                     }
@@ -219,7 +219,7 @@ impl Droid {
                     security_checkpoint_route
                         .iter()
                         .for_each(|door| self.controller.inputln(door));
-                    self.controller.run();
+                    self.controller.run().unwrap();
                     self.controller.outputs().clear();
                     // Now try to get through, returning the password that we should find on the other side
                     return self.try_verify_identity(security_checkpoint_route.last().unwrap());
@@ -1,4 +1,6 @@
 use std::fmt;
+use utils::automaton::{Automaton, Edge};
+use utils::grid::VecGrid;
 
 #[derive(PartialEq, Clone, Copy)]
 pub enum State {
@@ -17,12 +19,6 @@ impl fmt::Debug for State {
     }
 }
 
-#[derive(PartialEq, Clone, Copy)]
-enum Mode {
-    ImmediateNeighbour,
-    FirstVisibleSeat,
-}
-
 impl State {
     /// Create a new State from a char
     fn new(c: char) -> Self {
@@ -76,7 +72,8 @@ const fn get_pos(
     None
 }
 
-fn occupied_seats_from_pos(input: &Vec<Vec<State>>, pos: (usize, usize), mode: Mode) -> usize {
+/// Counts the occupied seats that are visible in each direction from `pos`
+fn occupied_seats_from_pos(input: &Vec<Vec<State>>, pos: (usize, usize)) -> usize {
     let width = input.first().unwrap().len();
     let height = input.len();
 
@@ -92,8 +89,8 @@ fn occupied_seats_from_pos(input: &Vec<Vec<State>>, pos: (usize, usize), mode: M
             // See if there is a seat here
             let (y, x) = neighbour.unwrap();
             let state = input[y][x];
-            if state == State::Floor && mode == Mode::FirstVisibleSeat {
-                // No seat and using first visible, increase range and try again
+            if state == State::Floor {
+                // No seat, increase range and try again
                 range += 1;
                 continue;
             }
@@ -119,7 +116,8 @@ fn count_occupied(input: &Vec<Vec<State>>) -> usize {
     occupied_seats
 }
 
-fn simulate_seating(input: &Vec<Vec<State>>, occupied_limit: usize, mode: Mode) -> Vec<Vec<State>> {
+/// Simulates people choosing seats based on the first seat they can see in each direction
+fn simulate_seating(input: &Vec<Vec<State>>, occupied_limit: usize) -> Vec<Vec<State>> {
     // Create two copies as we need to apply state changes simultaneously to all positions
     let mut previous: Vec<Vec<State>> = input.clone();
     let mut next: Vec<Vec<State>> = input.clone();
@@ -134,7 +132,7 @@ fn simulate_seating(input: &Vec<Vec<State>>, occupied_limit: usize, mode: Mode)
         for y in 0..height {
             for x in 0..width {
                 let old = previous[y][x];
-                let occupied_seats = occupied_seats_from_pos(&previous, (y, x), mode);
+                let occupied_seats = occupied_seats_from_pos(&previous, (y, x));
                 if old == State::Occupied && occupied_seats >= occupied_limit {
                     changed = true;
                     next[y][x] = State::Empty;
@@ -153,13 +151,25 @@ fn simulate_seating(input: &Vec<Vec<State>>, occupied_limit: usize, mode: Mode)
 
 #[aoc(day11, part1)]
 fn part1(input: &Vec<Vec<State>>) -> usize {
-    let layout = simulate_seating(input, 4, Mode::ImmediateNeighbour);
-    count_occupied(&layout)
+    // People only consider the seats immediately next to them
+    let automaton = Automaton::new(Edge::Fixed(State::Floor), |seat, neighbours: &[State]| {
+        let occupied = neighbours.iter().filter(|&&n| n == State::Occupied).count();
+        match seat {
+            State::Empty if occupied == 0 => State::Occupied,
+            State::Occupied if occupied >= 4 => State::Empty,
+            _ => seat,
+        }
+    });
+    let (layout, _) = automaton.run_until_stable(VecGrid::from(input.clone()));
+    layout
+        .values()
+        .filter(|&&seat| seat == State::Occupied)
+        .count()
 }
 
 #[aoc(day11, part2)]
 fn part2(input: &Vec<Vec<State>>) -> usize {
-    let layout = simulate_seating(input, 5, Mode::FirstVisibleSeat);
+    let layout = simulate_seating(input, 5);
     count_occupied(&layout)
 }
 
@@ -1,4 +1,4 @@
-use modinverse::modinverse;
+use utils::math::crt;
 
 #[aoc_generator(day13)]
 fn gen(input: &str) -> (i64, Vec<i64>) {
@@ -37,20 +37,6 @@ fn part1(input: &(i64, Vec<i64>)) -> f64 {
     min_wait_id_mult.round()
 }
 
-/// Calculates the CRT
-///
-/// `https://en.wikipedia.org/wiki/Chinese_remainder_theorem`
-fn chinese_remainder_theorem(input: &[(i64, i64)]) -> i64 {
-    let product = input.iter().map(|(_, m)| m).product::<i64>();
-    let mut sum = 0;
-    for (remainder, modulus) in input.iter() {
-        // Apply extended euclidean algorithm and sum up for all
-        let p = product / modulus;
-        sum += remainder * modinverse(p, *modulus).unwrap() * p
-    }
-    sum % product
-}
-
 /// Generates the offsets and periods needed for CRT from bus ids
 /// (we map offsets => remainders and periods => moduli)
 fn generate_offsets_periods(bus_ids: &[i64]) -> Vec<(i64, i64)> {
@@ -78,7 +64,8 @@ fn part2(input: &(i64, Vec<i64>)) -> i64 {
     // Use CRT to calculate the convergence point then subtract the
     // the number of buses - 1 (== max offset)
     // to get the time for the first bus (with 0 offset)
-    chinese_remainder_theorem(&generate_offsets_periods(&input.1)) - (input.1.len() - 1) as i64
+    let (convergence, _) = crt(&generate_offsets_periods(&input.1)).unwrap();
+    convergence - (input.1.len() - 1) as i64
 }
 
 #[cfg(test)]
@@ -87,10 +74,7 @@ mod tests {
 
     #[test]
     fn test_crt() {
-        assert_eq!(
-            chinese_remainder_theorem(&[(2, 3), (3, 5), (2, 7)].to_vec()),
-            23
-        );
+        assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
     }
 
     #[test]
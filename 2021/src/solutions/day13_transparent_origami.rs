@@ -1,6 +1,6 @@
 use itertools::Itertools;
 use utils::grid::Pos;
-use utils::ocr::{OcrString, Point};
+use utils::ocr::OcrString;
 
 type FoldInstruction = (Axis, isize);
 
@@ -65,7 +65,6 @@ fn part2(input: &Origami) -> String {
         .iter()
         .map(|&pos| input.folds.iter().fold(pos, |pos, x| origami_fold(pos, x)))
         .unique()
-        .map(|pos| Point::try_from(pos).unwrap())
         .collect::<OcrString>()
         .to_string()
 }
@@ -1,7 +1,7 @@
 use num_integer::Integer;
-use pathfinding::prelude::dijkstra;
 use utils::grid::Pos;
 use utils::grid::VecGrid;
+use utils::search::{dijkstra, vec_grid_successors};
 
 #[aoc_generator(day15)]
 fn gen(input: &str) -> VecGrid<u8> {
@@ -10,17 +10,12 @@ fn gen(input: &str) -> VecGrid<u8> {
 }
 
 /// Find the 'lowest risk' path from top left to bottom right corner based
-/// using pathfinding's dijkstra implementation
+/// using dijkstra where the cost of moving is the risk of the position moved to
 fn lowest_risk_path(map: &VecGrid<u8>) -> Option<(Vec<Pos>, usize)> {
     let target = Pos::new(map.width() - 1, map.height() - 1);
     dijkstra(
         &Pos::new(0, 0),
-        |&p| {
-            map.neighbours_ex(p)
-                .filter(|(_, _, risk)| risk.is_some())
-                .map(|(_, pos, risk)| (pos, risk.unwrap() as usize))
-                .collect::<Vec<(Pos, usize)>>()
-        },
+        vec_grid_successors(map, |_, risk| Some(risk as usize)),
         |p| *p == target,
     )
 }
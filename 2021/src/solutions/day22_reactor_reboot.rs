@@ -1,52 +1,9 @@
-use itertools::Itertools;
-use std::collections::HashMap;
+use utils::interval::{Cuboid, CuboidSet};
 
 #[derive(Debug, Clone)]
 struct Step {
     on: bool,
-    cube: Cube,
-}
-
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-struct Cube {
-    x: (isize, isize),
-    y: (isize, isize),
-    z: (isize, isize),
-}
-
-impl Cube {
-    /// Tests whether this cube is fully within the initialisation area
-    const fn within_initialisation_area(&self) -> bool {
-        self.z.0 >= -50
-            && self.z.1 <= 50
-            && self.y.0 >= -50
-            && self.y.1 <= 50
-            && self.x.0 >= -50
-            && self.x.1 <= 50
-    }
-
-    /// Calculates the intersection of this `Cube` with another
-    ///
-    /// Returns a new `Cube` if they intersect or None if they don't
-    fn intersection(&self, other: &Self) -> Option<Self> {
-        let (x, y, z) = [(self.x, other.x), (self.y, other.y), (self.z, other.z)]
-            .iter()
-            .map(|(a, b)| (isize::max(a.0, b.0), isize::min(a.1, b.1)))
-            .collect_tuple()
-            .unwrap();
-        if x.0 <= x.1 && y.0 <= y.1 && z.0 <= z.1 {
-            // Cubes intersect, return the intersection
-            Some(Self { x, y, z })
-        } else {
-            // Cubes don't intersect
-            None
-        }
-    }
-
-    /// Calculates the volume of this cube
-    const fn volume(&self) -> isize {
-        (self.x.1 - self.x.0 + 1) * (self.y.1 - self.y.0 + 1) * (self.z.1 - self.z.0 + 1)
-    }
+    cube: Cuboid<3>,
 }
 
 impl Step {
@@ -54,71 +11,31 @@ impl Step {
         let parts = s.split(&[' ', '=', '.', ','][..]).collect::<Vec<_>>();
         Self {
             on: parts[0] == "on",
-            cube: Cube {
-                x: (parts[2].parse().unwrap(), parts[4].parse().unwrap()),
-                y: (parts[6].parse().unwrap(), parts[8].parse().unwrap()),
-                z: (parts[10].parse().unwrap(), parts[12].parse().unwrap()),
-            },
+            cube: Cuboid::from_ranges([
+                parts[2].parse().unwrap()..=parts[4].parse().unwrap(),
+                parts[6].parse().unwrap()..=parts[8].parse().unwrap(),
+                parts[10].parse().unwrap()..=parts[12].parse().unwrap(),
+            ]),
         }
     }
 
-    /// Checks if this step is part of the initialization process
-    const fn initialization_step(&self) -> bool {
-        self.cube.within_initialisation_area()
+    /// Checks if this step is part of the initialization process (fully within the initialisation area)
+    fn initialization_step(&self) -> bool {
+        Cuboid::from_ranges([-50..=50, -50..=50, -50..=50]).contains_cuboid(&self.cube)
     }
 }
 
 /// From a list of `steps`, calculates how many cubes will be on at the end
 fn on_cubes<'a>(steps: impl Iterator<Item = &'a Step>) -> isize {
-    // Track on/off cubes in a HashMap of <Cube, State>
-    // Where state is 1 for on, 0 for cancelled out, and -1 for off (used when contained within an on cube)
-    // Rather than splitting cubes into sub cubes when they are intersected, we simply store
-    // a cube representing the intersection with a state such that it negates the pixels from the cube it intersected
-    // The final result is then the sum of the volume of on cubes - volume of off cubes
-    let mut cubes: HashMap<Cube, isize> = HashMap::new();
+    let mut reactor = CuboidSet::new();
     for step in steps {
-        let mut updates = HashMap::new();
-        let mut removals = Vec::new();
-        // For each step, work out what impact it has on the existing cubes
-        for (existing, state) in &cubes {
-            // See if this new step intersects with an existing one
-            if let Some(intersection) = existing.intersection(&step.cube) {
-                if intersection == *existing {
-                    // The new cube fully contains the existing one
-                    // Remove it the existing cube as no need to separately track it
-                    removals.push(existing.clone());
-                } else {
-                    // The new cube partially intersects the existing one
-                    // Cancel out the pixels representing the intersection
-                    *updates.entry(intersection).or_insert(0) -= state;
-                }
-            } // Doesn't intersect, no impact on existing cube
-        }
-        // If this is an on step, add a new cube representing it with pixels turned on
         if step.on {
-            *updates.entry(step.cube.clone()).or_insert(0) += 1;
-        }
-        // Remove all existing cubes that where fully contained within the new cube
-        for cube in removals {
-            cubes.remove(&cube);
-        }
-        // Add all of the new cubes we generated (and update existing ones if applicable) ready for the next step
-        for (cube, state) in updates {
-            if state == 0 {
-                // Cube has been canceled out (turned on then off again)
-                // Stop tracking it
-                cubes.remove(&cube);
-            } else {
-                // Cube is on or off (inside an on cube), update
-                *cubes.entry(cube).or_insert(0) += state;
-            }
+            reactor.insert(step.cube);
+        } else {
+            reactor.remove(step.cube);
         }
     }
-    // Final result is the sum of the volumes * on/off state
-    cubes
-        .iter()
-        .map(|(cube, &state)| cube.volume() * state)
-        .sum()
+    reactor.volume()
 }
 
 #[aoc_generator(day22)]
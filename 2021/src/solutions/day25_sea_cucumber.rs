@@ -1,8 +1,12 @@
-use utils::grid::{Pos, VecGrid};
+use utils::grid::{Direction, VecGrid, Wrap};
 
 #[aoc_generator(day25)]
 fn gen(input: &str) -> VecGrid<char> {
-    input.parse().unwrap()
+    // Cucumbers that move off one edge re-appear on the opposite one
+    input
+        .parse::<VecGrid<char>>()
+        .unwrap()
+        .with_wrap(Wrap::Both)
 }
 
 #[aoc(day25, part1)]
@@ -13,15 +17,16 @@ fn part1(input: &VecGrid<char>) -> usize {
     for step in 1.. {
         let mut moves = 0;
         for phase in ['>', 'v'] {
-            let mut next = VecGrid::new_sized('.', width, height);
+            let mut next = VecGrid::new_sized('.', width, height).with_wrap(Wrap::Both);
             for (pos, &cucumber) in grid.into_iter().filter(|(_, &x)| x != '.') {
                 if cucumber == phase {
                     // Work out where this cucumber would like to move to next
-                    let target = if cucumber == '>' {
-                        Pos::from(((pos.x + 1) % width as isize, pos.y))
+                    let direction = if cucumber == '>' {
+                        Direction::Right
                     } else {
-                        Pos::from((pos.x, (pos.y + 1) % height as isize))
+                        Direction::Down
                     };
+                    let target = grid.next_pos(pos, direction).unwrap();
                     if grid.get(target) == Some('.') {
                         // target is free - move
                         next[pos] = '.';
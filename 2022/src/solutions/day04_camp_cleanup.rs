@@ -1,19 +1,10 @@
 use std::ops::RangeInclusive;
 
 use itertools::Itertools;
+use utils::interval::{range_contains, range_overlaps};
 
 type Pair = (RangeInclusive<usize>, RangeInclusive<usize>);
 
-/// Returns True if a fully contains b
-fn contains(a: &RangeInclusive<usize>, b: &RangeInclusive<usize>) -> bool {
-    a.contains(b.start()) && a.contains(b.end())
-}
-
-/// Returns True if a does not include any values from b
-fn disjoint(a: &RangeInclusive<usize>, b: &RangeInclusive<usize>) -> bool {
-    !a.contains(b.start()) && !a.contains(b.end())
-}
-
 #[aoc_generator(day4)]
 fn gen(input: &str) -> Vec<Pair> {
     input
@@ -34,15 +25,15 @@ fn gen(input: &str) -> Vec<Pair> {
 fn part1(input: &[Pair]) -> usize {
     input // Count the number of pair where one is fully contained by the over
         .iter()
-        .filter(|pair| contains(&pair.0, &pair.1) || contains(&pair.1, &pair.0))
+        .filter(|pair| range_contains(&pair.0, &pair.1) || range_contains(&pair.1, &pair.0))
         .count()
 }
 
 #[aoc(day4, part2)]
 fn part2(input: &[Pair]) -> usize {
-    input // Count the number of pairs where there is some overlap (not disjoint)
+    input // Count the number of pairs where there is some overlap
         .iter()
-        .filter(|pair| !disjoint(&pair.0, &pair.1) || !disjoint(&pair.1, &pair.0))
+        .filter(|pair| range_overlaps(&pair.0, &pair.1))
         .count()
 }
 
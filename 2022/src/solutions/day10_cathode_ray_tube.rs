@@ -84,7 +84,16 @@ fn part2(input: &Vec<Instr>) -> OcrString {
     let mut crt_row: isize = 0;
     let mut crt_column: isize = 0;
 
-    while cpu.step() {
+    loop {
+        // Check to see if the sprite aligns (+/- 1 column)
+        // Drawn before stepping so that the first pixel uses the initial register
+        if crt_column >= cpu.register - 1 && crt_column <= cpu.register + 1 {
+            // Draw!
+            crt.insert(Pos::from((crt_column, crt_row)), '#');
+        }
+        if !cpu.step() {
+            break;
+        }
         // Advance position
         crt_column += 1;
         if crt_column == 40 {
@@ -96,13 +105,8 @@ fn part2(input: &Vec<Instr>) -> OcrString {
                 crt_row = 0;
             }
         }
-        // Check to see if the sprite aligns (+/- 1 row)
-        if crt_column >= cpu.register - 1 && crt_column <= cpu.register + 1 {
-            // Draw!
-            crt.insert(Pos::from((crt_column, crt_row)), '#');
-        }
     }
-    crt.keys().map(|pos| (pos.x, pos.y)).collect()
+    OcrString::from(&crt)
 }
 
 #[cfg(test)]
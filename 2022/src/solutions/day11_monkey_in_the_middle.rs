@@ -1,36 +1,27 @@
 use std::{collections::VecDeque, str::FromStr};
 
 use itertools::Itertools;
-use reformation::Reformation;
+use lazy_static::lazy_static;
+use utils::expr::{Associativity, Expr, Grammar};
 
-#[derive(Reformation, Debug, Clone, Copy)]
-enum Operation {
-    #[reformation(r"old \+ old")]
-    Double,
-    #[reformation(r"old \* old")]
-    Square,
-    #[reformation(r"old \+ {}")]
-    Add(u64),
-    #[reformation(r"old \* {}")]
-    Mul(u64),
+lazy_static! {
+    /// Operations that a monkey can perform on the worry level of an item
+    static ref OPERATIONS: Grammar<u64> = Grammar::new()
+        .binary("+", 1, Associativity::Left, |a, b| a + b)
+        .binary("*", 2, Associativity::Left, |a, b| a * b);
 }
 
-impl Operation {
-    /// Applies the operation to an item, returning it's new worry level
-    const fn apply(&self, item: u64) -> u64 {
-        match self {
-            Self::Double => item * 2,
-            Self::Square => item * item,
-            Self::Add(value) => item + *value,
-            Self::Mul(value) => item * *value,
-        }
-    }
+/// Applies an operation to an item, returning it's new worry level
+fn apply(operation: &Expr<u64>, item: u64) -> u64 {
+    OPERATIONS
+        .evaluate(operation, &mut |name| (name == "old").then_some(item))
+        .unwrap()
 }
 
 #[derive(Debug, Clone)]
 struct Monkey {
     items: VecDeque<u64>,
-    operation: Operation,
+    operation: Expr<u64>,
     test: u64,
     destination: [usize; 2],
 }
@@ -48,7 +39,7 @@ impl FromStr for Monkey {
                 .iter()
                 .flat_map(|item| item.parse::<u64>())
                 .collect(),
-            operation: Operation::parse(&parts[2][3..].join(" ")).unwrap(),
+            operation: OPERATIONS.parse(&parts[2][3..].join(" ")).unwrap(),
             test: parts[3][3].parse().unwrap(),
             destination: [parts[4][5].parse().unwrap(), parts[5][5].parse().unwrap()],
         })
@@ -63,7 +54,7 @@ impl Monkey {
             .map(|item| {
                 // Calculate the new worry level based on the operation, the amount of relief we
                 // get after each item is inspected, and then warp it if it goes over the max limit
-                let worry_level = (self.operation.apply(item) / RELIEF_FACTOR) % worry_limit;
+                let worry_level = (apply(&self.operation, item) / RELIEF_FACTOR) % worry_limit;
                 // Which monkey gets it next depends on the outcome of the test
                 (
                     self.destination[usize::from(worry_level % self.test != 0)],
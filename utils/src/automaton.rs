@@ -0,0 +1,341 @@
+//! A module for running cellular automata (e.g. Conway's game of life) over grids
+//!
+//! The `Automaton` holds the rule to apply to each cell along with how to treat the
+//! edges of the grid, and can then be used to step either a `VecGrid` (using an `Edge`)
+//! or `MapGrid` (using a `MapEdge`) one generation at a time or until it reaches a stable state.
+
+use crate::grid::{MapGrid, Pos, VecGrid};
+use std::collections::HashSet;
+use std::marker::PhantomData;
+
+/// Which cells are considered to be neighbours
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Neighbours {
+    /// The 4 cells that are up, down, left and right
+    Four,
+    /// The 8 cells that surround the cell including diagonals
+    Eight,
+}
+
+/// How cells beyond the edge of a `VecGrid` are handled
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Edge<V> {
+    /// Cells beyond the edge always have the supplied value
+    Fixed(V),
+    /// Cells beyond the edge wrap around to the opposite side of the grid
+    Wrap,
+    /// The grid grows as needed with cells beyond the edge starting with the supplied value
+    ///
+    /// The grid will grow by 1 cell on each side in any generation where a cell beyond
+    /// the edge changes (so positions get shifted by (1, 1))
+    Grow(V),
+}
+
+/// How cells that aren't stored in a `MapGrid` are handled
+///
+/// Unlike `Edge` there is no way to wrap around as a `MapGrid` has no fixed size
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum MapEdge<V> {
+    /// Cells that aren't stored always have the supplied value and only stored cells get updated
+    Fixed(V),
+    /// The grid is treated as infinite with cells that aren't stored having the supplied value
+    ///
+    /// Only cells that do not have the supplied value are stored
+    Grow(V),
+}
+
+/// Cellular automaton that applies a `rule` to every cell in a grid each generation
+///
+/// The rule is passed the current value of the cell along with the values of its neighbours
+/// and should return the new value of the cell. The type of `edge` decides which grids the
+/// automaton can step (`Edge` for a `VecGrid` and `MapEdge` for a `MapGrid`).
+pub struct Automaton<V, F, E = Edge<V>> {
+    rule: F,
+    edge: E,
+    neighbours: Neighbours,
+    value: PhantomData<fn(V) -> V>,
+}
+
+/// Grids that can be stepped by an `Automaton`
+pub trait Cells<V>: Sized {
+    /// How the grid handles cells beyond its edge
+    type Edge;
+
+    /// Creates the next generation of the grid, returning it along with the number of cells that changed
+    fn step<F: Fn(V, &[V]) -> V>(&self, automaton: &Automaton<V, F, Self::Edge>) -> (Self, usize);
+}
+
+impl<V, F, E> Automaton<V, F, E>
+where
+    V: Copy + PartialEq,
+    F: Fn(V, &[V]) -> V,
+{
+    /// Creates a new `Automaton` that uses all 8 neighbours
+    pub const fn new(edge: E, rule: F) -> Self {
+        Self {
+            rule,
+            edge,
+            neighbours: Neighbours::Eight,
+            value: PhantomData,
+        }
+    }
+
+    /// Changes which cells are considered to be neighbours
+    pub const fn with_neighbours(mut self, neighbours: Neighbours) -> Self {
+        self.neighbours = neighbours;
+        self
+    }
+
+    /// Creates the next generation of the supplied grid
+    pub fn step<G: Cells<V, Edge = E>>(&self, grid: &G) -> G {
+        grid.step(self).0
+    }
+
+    /// Runs the automaton for the specified number of generations
+    pub fn run<G: Cells<V, Edge = E>>(&self, grid: G, generations: usize) -> G {
+        (0..generations).fold(grid, |grid, _| grid.step(self).0)
+    }
+
+    /// Runs the automaton until no cells change
+    ///
+    /// Returns the stable grid along with the number of generations it took to reach it
+    /// (not counting the final generation where nothing changed)
+    pub fn run_until_stable<G: Cells<V, Edge = E>>(&self, mut grid: G) -> (G, usize) {
+        let mut generations = 0;
+        loop {
+            let (next, changed) = grid.step(self);
+            if changed == 0 {
+                return (next, generations);
+            }
+            grid = next;
+            generations += 1;
+        }
+    }
+
+    /// Gets the positions of the neighbours of the supplied position
+    fn neighbour_positions(&self, pos: Pos) -> Vec<Pos> {
+        match self.neighbours {
+            Neighbours::Four => pos.neighbours().collect(),
+            Neighbours::Eight => pos.neighbours8().collect(),
+        }
+    }
+}
+
+/// Wraps `value` so that it is within `min..=max`
+const fn wrap(value: isize, min: isize, max: isize) -> isize {
+    min + (value - min).rem_euclid(max - min + 1)
+}
+
+impl<V: Copy + PartialEq> Cells<V> for VecGrid<V> {
+    type Edge = Edge<V>;
+
+    fn step<F: Fn(V, &[V]) -> V>(&self, automaton: &Automaton<V, F, Self::Edge>) -> (Self, usize) {
+        if self.height() == 0 || self.width() == 0 {
+            return (self.clone(), 0);
+        }
+        let (width, height) = (self.width() as isize, self.height() as isize);
+        let lookup = |pos: Pos| match automaton.edge {
+            Edge::Fixed(default) | Edge::Grow(default) => self.get(pos).unwrap_or(default),
+            Edge::Wrap => {
+                self[Pos {
+                    x: wrap(pos.x, 0, width - 1),
+                    y: wrap(pos.y, 0, height - 1),
+                }]
+            }
+        };
+        let mut neighbours = Vec::with_capacity(8);
+        let mut next_value = |pos: Pos, current: V| {
+            neighbours.clear();
+            neighbours.extend(automaton.neighbour_positions(pos).into_iter().map(lookup));
+            (automaton.rule)(current, &neighbours)
+        };
+        // When growing, the new grid only has an extra cell on every side if one of them changes
+        let shift = match automaton.edge {
+            Edge::Grow(default) => {
+                let grows = (-1..=height)
+                    .flat_map(|y| (-1..=width).map(move |x| Pos { x, y }))
+                    .filter(|&pos| !self.contains(pos))
+                    .any(|pos| next_value(pos, default) != default);
+                isize::from(grows)
+            }
+            _ => 0,
+        };
+        let mut next = Self::new_sized(
+            self[Pos::new(0, 0)],
+            (width + 2 * shift) as usize,
+            (height + 2 * shift) as usize,
+        );
+        let mut changed = 0;
+        for y in -shift..height + shift {
+            for x in -shift..width + shift {
+                let pos = Pos { x, y };
+                let current = self.get(pos).unwrap_or_else(|| lookup(pos));
+                let value = next_value(pos, current);
+                if value != current {
+                    changed += 1;
+                }
+                next[pos + (shift, shift)] = value;
+            }
+        }
+        (next, changed)
+    }
+}
+
+impl<V: Copy + PartialEq> Cells<V> for MapGrid<V> {
+    type Edge = MapEdge<V>;
+
+    fn step<F: Fn(V, &[V]) -> V>(&self, automaton: &Automaton<V, F, Self::Edge>) -> (Self, usize) {
+        let mut changed = 0;
+        let mut next = Self::new();
+        match automaton.edge {
+            MapEdge::Fixed(default) => {
+                // Only cells that exist get updated
+                for (&pos, &current) in self.iter() {
+                    let neighbours = automaton
+                        .neighbour_positions(pos)
+                        .into_iter()
+                        .map(|n| self.get(&n).copied().unwrap_or(default))
+                        .collect::<Vec<_>>();
+                    let value = (automaton.rule)(current, &neighbours);
+                    if value != current {
+                        changed += 1;
+                    }
+                    next.insert(pos, value);
+                }
+            }
+            MapEdge::Grow(default) => {
+                // Any cell next to one that is stored could change, but cells
+                // with the default value don't need to be stored
+                let candidates = self
+                    .keys()
+                    .flat_map(|&pos| std::iter::once(pos).chain(automaton.neighbour_positions(pos)))
+                    .collect::<HashSet<_>>();
+                for pos in candidates {
+                    let current = self.get(&pos).copied().unwrap_or(default);
+                    let neighbours = automaton
+                        .neighbour_positions(pos)
+                        .into_iter()
+                        .map(|n| self.get(&n).copied().unwrap_or(default))
+                        .collect::<Vec<_>>();
+                    let value = (automaton.rule)(current, &neighbours);
+                    if value != current {
+                        changed += 1;
+                    }
+                    if value != default {
+                        next.insert(pos, value);
+                    }
+                }
+            }
+        }
+        (next, changed)
+    }
+}
+
+/// Rule for Conway's game of life where `true` is alive
+pub fn game_of_life(alive: bool, neighbours: &[bool]) -> bool {
+    matches!(
+        (alive, neighbours.iter().filter(|&&n| n).count()),
+        (true, 2 | 3) | (false, 3)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_bool(s: &str) -> VecGrid<bool> {
+        VecGrid::from(
+            s.lines()
+                .map(|line| line.chars().map(|c| c == '#').collect())
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    fn alive(grid: &VecGrid<bool>) -> usize {
+        grid.values().filter(|&&v| v).count()
+    }
+
+    #[test]
+    fn test_fixed_2015_day18_example() {
+        let grid = to_bool(".#.#.#\n...##.\n#....#\n..#...\n#.#..#\n####..");
+        let automaton = Automaton::new(Edge::Fixed(false), game_of_life);
+        assert_eq!(alive(&automaton.run(grid, 4)), 4);
+    }
+
+    #[test]
+    fn test_wrap_glider() {
+        // A glider on a torus returns to the same place after 4 * size generations
+        let grid = to_bool(".#....\n..#...\n###...\n......\n......\n......");
+        let automaton = Automaton::new(Edge::Wrap, game_of_life);
+        let result = automaton.run(grid.clone(), 24);
+        assert!(result.indexes().all(|pos| result.get(pos) == grid.get(pos)));
+    }
+
+    #[test]
+    fn test_grow_vec_and_map_agree() {
+        let grid = to_bool(".#.\n..#\n###");
+        let vec = Automaton::new(Edge::Grow(false), game_of_life).run(grid.clone(), 8);
+        let map: MapGrid<bool> = grid.into_iter().map(|(p, v)| (p, *v)).collect();
+        let map = Automaton::new(MapEdge::Grow(false), game_of_life).run(map, 8);
+        assert_eq!(map.keys().count(), 5);
+        assert_eq!(alive(&vec), 5);
+        // The glider has moved 2 cells down and right
+        let shift = (vec.width() as isize - 3) / 2;
+        assert!(map.keys().all(|&p| vec[p + (shift, shift)]));
+        assert!(map.keys().all(|&p| grid[p - (2_isize, 2_isize)]));
+    }
+
+    #[test]
+    fn test_grow_only_when_needed() {
+        // A blinker stays within a 3x3 grid
+        let automaton = Automaton::new(Edge::Grow(false), game_of_life);
+        let blinker = automaton.run(to_bool(".#.\n.#.\n.#."), 101);
+        assert_eq!((blinker.width(), blinker.height()), (3, 3));
+        assert_eq!(alive(&blinker), 3);
+        let (empty, changed) = VecGrid::<bool>::default().step(&automaton);
+        assert_eq!((empty.height(), changed), (0, 0));
+    }
+
+    #[test]
+    fn test_map_grid_fixed() {
+        // Only the stored cells are updated, so (1, -1) stays empty despite having 3 live neighbours
+        let grid: MapGrid<bool> = [(0, 0), (1, 0), (2, 0), (1, 1)]
+            .into_iter()
+            .map(|(x, y)| (Pos::new(x, y), true))
+            .collect();
+        let (next, changed) = grid.step(&Automaton::new(MapEdge::Fixed(false), game_of_life));
+        assert_eq!(next.keys().count(), 4);
+        assert_eq!(next.values().filter(|&&v| v).count(), 4);
+        assert_eq!(changed, 0);
+    }
+
+    #[test]
+    fn test_run_until_stable_2020_day11_example() {
+        let grid: VecGrid<char> = "L.LL.LL.LL\nLLLLLLL.LL\nL.L.L..L..\nLLLL.LL.LL\nL.LL.LL.LL\nL.LLLLL.LL\n..L.L.....\nLLLLLLLLLL\nL.LLLLLL.L\nL.LLLLL.LL"
+            .parse()
+            .unwrap();
+        let automaton = Automaton::new(Edge::Fixed('.'), |seat, neighbours: &[char]| {
+            let occupied = neighbours.iter().filter(|&&c| c == '#').count();
+            match seat {
+                'L' if occupied == 0 => '#',
+                '#' if occupied >= 4 => 'L',
+                _ => seat,
+            }
+        });
+        let (stable, generations) = automaton.run_until_stable(grid);
+        assert_eq!(generations, 5);
+        assert_eq!(stable.values().filter(|&&c| c == '#').count(), 37);
+    }
+
+    #[test]
+    fn test_four_neighbours() {
+        let grid = to_bool("...\n.#.\n...");
+        let spread = Automaton::new(Edge::Fixed(false), |alive, neighbours: &[bool]| {
+            alive || neighbours.iter().any(|&n| n)
+        })
+        .with_neighbours(Neighbours::Four);
+        let result = spread.step(&grid);
+        assert_eq!(alive(&result), 5);
+        assert!(!result[Pos::new(0, 0)]);
+    }
+}
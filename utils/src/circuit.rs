@@ -0,0 +1,277 @@
+//! A module for evaluating circuits of named expressions (e.g. wires driven by logic gates)
+//!
+//! Each wire is driven by an `Expr` whose variables are the names of other wires. Wires are
+//! evaluated in topological order so that every input is known before it is needed, and the
+//! computed signals are kept so that overriding a wire only re-evaluates the wires that
+//! depend on it.
+
+use crate::expr::{Expr, ExprError, Grammar};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::io::{self, Write};
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum CircuitError {
+    #[error("cycle detected: {}", .path.join(" -> "))]
+    Cycle { path: Vec<String> },
+    #[error("wire '{wire}' used by '{used_by}' has no input")]
+    UndefinedWire { wire: String, used_by: String },
+    #[error(transparent)]
+    Expr(#[from] ExprError),
+}
+
+/// Directed acyclic graph of wires and the expressions that drive them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Circuit<T> {
+    wires: HashMap<String, Expr<T>>,
+    signals: HashMap<String, T>,
+}
+
+impl<T> Circuit<T> {
+    /// Creates a new `Circuit` without any wires
+    pub fn new() -> Self {
+        Self {
+            wires: HashMap::new(),
+            signals: HashMap::new(),
+        }
+    }
+
+    /// Connects `expr` to `wire` (replacing anything that was already connected to it)
+    ///
+    /// The signals of the wire and all wires that depend on it will be re-evaluated
+    /// next time `evaluate` is called
+    pub fn set(&mut self, wire: &str, expr: Expr<T>) {
+        self.invalidate(wire);
+        self.wires.insert(wire.to_owned(), expr);
+    }
+
+    /// Gets the expression connected to a wire
+    pub fn get(&self, wire: &str) -> Option<&Expr<T>> {
+        self.wires.get(wire)
+    }
+
+    /// Gets the signal of a wire (if it has been evaluated)
+    pub fn signal(&self, wire: &str) -> Option<&T> {
+        self.signals.get(wire)
+    }
+
+    /// Gets the wires sorted so that every wire comes after all of its inputs
+    ///
+    /// Fails if any wires depend on themselves or on a wire that has no input
+    pub fn order(&self) -> Result<Vec<&str>, CircuitError> {
+        let mut wires = self.wires.keys().map(String::as_str).collect::<Vec<_>>();
+        wires.sort_unstable();
+        let mut order = Vec::with_capacity(wires.len());
+        let mut visited = HashSet::new();
+        for wire in wires {
+            self.visit(wire, &mut Vec::new(), &mut visited, &mut order)?;
+        }
+        Ok(order)
+    }
+
+    /// Evaluates the signals of all wires that haven't already been evaluated
+    pub fn evaluate(&mut self, grammar: &Grammar<T>) -> Result<(), CircuitError>
+    where
+        T: Clone,
+    {
+        let pending = self
+            .order()?
+            .into_iter()
+            .filter(|wire| !self.signals.contains_key(*wire))
+            .map(str::to_owned)
+            .collect::<Vec<_>>();
+        for wire in pending {
+            let signal = grammar.evaluate(&self.wires[&wire], &mut |input| {
+                self.signals.get(input).cloned()
+            })?;
+            self.signals.insert(wire, signal);
+        }
+        Ok(())
+    }
+
+    /// Writes the circuit as a Graphviz DOT digraph with an edge from each input to the wires it drives
+    pub fn write_dot(&self, mut w: impl Write) -> io::Result<()>
+    where
+        T: Display,
+    {
+        let mut wires = self.wires.iter().collect::<Vec<_>>();
+        wires.sort_unstable_by_key(|(wire, _)| *wire);
+        writeln!(w, "digraph circuit {{")?;
+        for (wire, expr) in &wires {
+            let label = match self.signals.get(*wire) {
+                Some(signal) => format!("{} = {}\n{}", wire, expr, signal),
+                None => format!("{} = {}", wire, expr),
+            };
+            writeln!(w, "    {:?} [label={:?}];", wire, label)?;
+        }
+        for (wire, expr) in &wires {
+            let mut inputs = expr.variables();
+            inputs.sort_unstable();
+            inputs.dedup();
+            for input in inputs {
+                writeln!(w, "    {:?} -> {:?};", input, wire)?;
+            }
+        }
+        writeln!(w, "}}")?;
+        w.flush()
+    }
+
+    /// Depth first search that adds `wire` to `order` after all of its inputs
+    ///
+    /// `path` holds the wires currently being visited so that cycles can be reported
+    fn visit<'a>(
+        &'a self,
+        wire: &'a str,
+        path: &mut Vec<&'a str>,
+        visited: &mut HashSet<&'a str>,
+        order: &mut Vec<&'a str>,
+    ) -> Result<(), CircuitError> {
+        if visited.contains(wire) {
+            return Ok(());
+        }
+        if let Some(start) = path.iter().position(|w| *w == wire) {
+            let mut cycle = path[start..]
+                .iter()
+                .map(|w| (*w).to_owned())
+                .collect::<Vec<_>>();
+            cycle.push(wire.to_owned());
+            return Err(CircuitError::Cycle { path: cycle });
+        }
+        path.push(wire);
+        for input in self.wires[wire].variables() {
+            if !self.wires.contains_key(input) {
+                return Err(CircuitError::UndefinedWire {
+                    wire: input.to_owned(),
+                    used_by: wire.to_owned(),
+                });
+            }
+            self.visit(input, path, visited, order)?;
+        }
+        path.pop();
+        visited.insert(wire);
+        order.push(wire);
+        Ok(())
+    }
+
+    /// Discards the signals of `wire` and all wires that depend on it
+    fn invalidate(&mut self, wire: &str) {
+        let mut dependents: HashMap<&str, Vec<&str>> = HashMap::new();
+        for (output, expr) in &self.wires {
+            for input in expr.variables() {
+                dependents.entry(input).or_default().push(output);
+            }
+        }
+        let mut queue = vec![wire];
+        while let Some(wire) = queue.pop() {
+            if self.signals.remove(wire).is_some() {
+                queue.extend(dependents.get(wire).into_iter().flatten());
+            }
+        }
+    }
+}
+
+impl<T> Default for Circuit<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> FromIterator<(String, Expr<T>)> for Circuit<T> {
+    fn from_iter<I: IntoIterator<Item = (String, Expr<T>)>>(iter: I) -> Self {
+        Self {
+            wires: iter.into_iter().collect(),
+            signals: HashMap::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expr::Associativity;
+
+    fn gates() -> Grammar<u16> {
+        Grammar::new()
+            .binary("AND", 1, Associativity::Left, |a, b| a & b)
+            .binary("OR", 1, Associativity::Left, |a, b| a | b)
+            .prefix("NOT", 2, |a| !a)
+    }
+
+    fn circuit(grammar: &Grammar<u16>, connections: &[(&str, &str)]) -> Circuit<u16> {
+        connections
+            .iter()
+            .map(|(wire, expr)| ((*wire).to_owned(), grammar.parse(expr).unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn test_evaluate_and_override() {
+        let grammar = gates();
+        let mut circuit = circuit(
+            &grammar,
+            &[
+                ("d", "x AND y"),
+                ("x", "123"),
+                ("e", "x OR y"),
+                ("y", "456"),
+                ("a", "d OR z"),
+                ("z", "NOT 0"),
+            ],
+        );
+        let order = circuit.order().unwrap();
+        assert_eq!(order, vec!["x", "y", "d", "z", "a", "e"]);
+        assert_eq!(circuit.signal("a"), None);
+        circuit.evaluate(&grammar).unwrap();
+        assert_eq!(circuit.signal("d"), Some(&72));
+        assert_eq!(circuit.signal("e"), Some(&507));
+        assert_eq!(circuit.signal("a"), Some(&65535));
+        // Overriding x invalidates everything downstream of it but nothing else
+        circuit.set("x", Expr::Value(7));
+        assert_eq!(circuit.signal("e"), None);
+        assert_eq!(circuit.signal("a"), None);
+        assert_eq!(circuit.signal("z"), Some(&65535));
+        assert_eq!(circuit.signal("y"), Some(&456));
+        circuit.evaluate(&grammar).unwrap();
+        assert_eq!(circuit.signal("d"), Some(&0));
+        assert_eq!(circuit.signal("e"), Some(&463));
+    }
+
+    #[test]
+    fn test_invalid_circuits() {
+        let grammar = gates();
+        let mut looped = circuit(&grammar, &[("a", "b AND c"), ("b", "c"), ("c", "NOT a")]);
+        let error = looped.evaluate(&grammar).unwrap_err();
+        assert_eq!(
+            error,
+            CircuitError::Cycle {
+                path: vec![
+                    "a".to_owned(),
+                    "b".to_owned(),
+                    "c".to_owned(),
+                    "a".to_owned()
+                ]
+            }
+        );
+        assert_eq!(error.to_string(), "cycle detected: a -> b -> c -> a");
+        let undefined = circuit(&grammar, &[("a", "b AND c"), ("b", "1")]);
+        assert_eq!(
+            undefined.order().unwrap_err().to_string(),
+            "wire 'c' used by 'a' has no input"
+        );
+    }
+
+    #[test]
+    fn test_dot() {
+        let grammar = gates();
+        let mut circuit = circuit(&grammar, &[("x", "1"), ("b", "NOT x AND x")]);
+        circuit.evaluate(&grammar).unwrap();
+        circuit.set("x", Expr::Value(3));
+        let mut dot = Vec::new();
+        circuit.write_dot(&mut dot).unwrap();
+        assert_eq!(
+            String::from_utf8(dot).unwrap(),
+            "digraph circuit {\n    \"b\" [label=\"b = (NOT x AND x)\"];\n    \"x\" [label=\"x = 3\"];\n    \"x\" -> \"b\";\n}\n"
+        );
+    }
+}
@@ -0,0 +1,179 @@
+//! A module for detecting cycles in sequences of states
+//!
+//! All functions take an initial state `x0` and a function `f` that produces the next
+//! state from the current one, and find `mu` (the index of the first state that is part
+//! of the cycle) and `lambda` (the length of the cycle).
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Uses Floyd's tortoise and hare algorithm to find (mu, lambda)
+///
+/// Only needs to store a couple of states at a time, but calls `f` more often
+/// than `brent` or `find_cycle`.
+pub fn floyd<S, F>(x0: S, mut f: F) -> (usize, usize)
+where
+    S: Clone + Eq,
+    F: FnMut(&S) -> S,
+{
+    // Find a repetition x_i = x_2i with the hare moving twice as fast as the tortoise
+    let mut tortoise = f(&x0);
+    let mut hare = f(&tortoise);
+    while tortoise != hare {
+        tortoise = f(&tortoise);
+        hare = f(&hare);
+        hare = f(&hare);
+    }
+    // Find the position of the first repetition by restarting the tortoise
+    let mut mu = 0;
+    tortoise = x0;
+    while tortoise != hare {
+        tortoise = f(&tortoise);
+        hare = f(&hare);
+        mu += 1;
+    }
+    // Find the length of the cycle by moving the hare until it is back with the tortoise
+    let mut lambda = 1;
+    hare = f(&tortoise);
+    while tortoise != hare {
+        hare = f(&hare);
+        lambda += 1;
+    }
+    (mu, lambda)
+}
+
+/// Uses Brent's algorithm to find (mu, lambda)
+///
+/// Only needs to store a couple of states at a time.
+pub fn brent<S, F>(x0: S, mut f: F) -> (usize, usize)
+where
+    S: Clone + Eq,
+    F: FnMut(&S) -> S,
+{
+    // Search successive powers of two for the length of the cycle
+    let mut power = 1;
+    let mut lambda = 1;
+    let mut tortoise = x0.clone();
+    let mut hare = f(&x0);
+    while tortoise != hare {
+        if power == lambda {
+            tortoise = hare.clone();
+            power *= 2;
+            lambda = 0;
+        }
+        hare = f(&hare);
+        lambda += 1;
+    }
+    // Find the position of the first repetition by starting the hare lambda ahead
+    tortoise = x0.clone();
+    hare = x0;
+    for _ in 0..lambda {
+        hare = f(&hare);
+    }
+    let mut mu = 0;
+    while tortoise != hare {
+        tortoise = f(&tortoise);
+        hare = f(&hare);
+        mu += 1;
+    }
+    (mu, lambda)
+}
+
+/// Finds (mu, lambda) by recording every state seen until one repeats
+///
+/// Calls `f` the minimum number of times (mu + lambda) at the expense of storing every state
+pub fn find_cycle<S, F>(x0: S, f: F) -> (usize, usize)
+where
+    S: Clone + Eq + Hash,
+    F: FnMut(&S) -> S,
+{
+    let (mu, lambda, _) = find_cycle_with_history(x0, f, None);
+    (mu, lambda)
+}
+
+/// Gets the state after `n` steps, using cycle detection to skip ahead
+/// rather than calling `step` `n` times
+///
+/// If the sequence doesn't repeat within `n` steps then `step` is called `n` times and every
+/// state is kept, so for a very large `n` this may exhaust memory and abort the process
+pub fn fast_forward<S, F>(x0: S, step: F, n: usize) -> S
+where
+    S: Clone + Eq + Hash,
+    F: FnMut(&S) -> S,
+{
+    let (mu, lambda, mut history) = find_cycle_with_history(x0, step, Some(n));
+    if n < history.len() {
+        // Reached before the cycle was found (or found within the history)
+        history.swap_remove(n)
+    } else {
+        history.swap_remove(mu + (n - mu) % lambda)
+    }
+}
+
+/// Records every state until one repeats (or `limit` steps have been taken)
+///
+/// Returns (mu, lambda, history) where the history contains all states up to the end of the first cycle
+fn find_cycle_with_history<S, F>(x0: S, mut f: F, limit: Option<usize>) -> (usize, usize, Vec<S>)
+where
+    S: Clone + Eq + Hash,
+    F: FnMut(&S) -> S,
+{
+    let mut seen = HashMap::from([(x0.clone(), 0)]);
+    let mut history = vec![x0];
+    loop {
+        let index = history.len();
+        if limit.is_some_and(|limit| limit < index) {
+            // Reached the required step before finding the cycle
+            return (0, 0, history);
+        }
+        let next = f(&history[index - 1]);
+        if let Some(&mu) = seen.get(&next) {
+            return (mu, index - mu, history);
+        }
+        history.push(next.clone());
+        seen.insert(next, index);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Sequence 0, 1, ..., 9, 3, 4, ... with mu = 3 and lambda = 7
+    fn next(x: &usize) -> usize {
+        if *x == 9 {
+            3
+        } else {
+            x + 1
+        }
+    }
+
+    #[test]
+    fn test_algorithms_agree() {
+        assert_eq!(floyd(0, next), (3, 7));
+        assert_eq!(brent(0, next), (3, 7));
+        assert_eq!(find_cycle(0, next), (3, 7));
+        // Starting within the cycle
+        assert_eq!(floyd(5, next), (0, 7));
+        assert_eq!(brent(5, next), (0, 7));
+        assert_eq!(find_cycle(5, next), (0, 7));
+        // Fixed point
+        assert_eq!(brent(1, |_: &usize| 1), (0, 1));
+        assert_eq!(floyd(0, |_: &usize| 1), (1, 1));
+    }
+
+    #[test]
+    fn test_fast_forward() {
+        let mut calls = 0;
+        let counted = |x: &usize| {
+            calls += 1;
+            next(x)
+        };
+        assert_eq!(fast_forward(0, counted, 1_000_000_000), 6);
+        assert_eq!(calls, 10);
+        for n in 0..30 {
+            let expected = (0..n).fold(0, |x, _| next(&x));
+            assert_eq!(fast_forward(0, next, n), expected);
+        }
+    }
+}
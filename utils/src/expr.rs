@@ -0,0 +1,484 @@
+//! A module for parsing and evaluating infix expressions
+//!
+//! A `Grammar` is a table of prefix and binary operators (symbol, precedence and associativity)
+//! that is used to parse expressions such as `1 + 2 * 3` or `x LSHIFT 2` into an `Expr` tree
+//! using Pratt parsing. Operands are either values (anything that parses as `T`) or named
+//! variables which are supplied when the expression is evaluated.
+
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ExprError {
+    #[error("unexpected char '{c}' at position {position}")]
+    UnexpectedChar { c: char, position: usize },
+    #[error("unexpected token '{token}' at position {position}")]
+    UnexpectedToken { token: String, position: usize },
+    #[error("unexpected end of expression")]
+    UnexpectedEnd,
+    #[error("unknown variable '{name}'")]
+    UnknownVariable { name: String },
+    #[error("unknown operator '{symbol}'")]
+    UnknownOperator { symbol: String },
+}
+
+/// Order in which a sequence of operators with the same precedence are applied
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Associativity {
+    /// `a - b - c` is `(a - b) - c`
+    Left,
+    /// `a ^ b ^ c` is `a ^ (b ^ c)`
+    Right,
+}
+
+/// Parsed expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr<T> {
+    Value(T),
+    Variable(String),
+    Prefix(&'static str, Box<Expr<T>>),
+    Binary(&'static str, Box<Expr<T>>, Box<Expr<T>>),
+}
+
+impl<T> Expr<T> {
+    /// Gets the names of all variables used by the expression (in the order they appear)
+    pub fn variables(&self) -> Vec<&str> {
+        match self {
+            Self::Value(_) => Vec::new(),
+            Self::Variable(name) => vec![name.as_str()],
+            Self::Prefix(_, operand) => operand.variables(),
+            Self::Binary(_, lhs, rhs) => {
+                let mut variables = lhs.variables();
+                variables.extend(rhs.variables());
+                variables
+            }
+        }
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Expr<T> {
+    /// Writes the expression with every binary operation in parentheses
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Value(value) => write!(f, "{}", value),
+            Self::Variable(name) => write!(f, "{}", name),
+            Self::Prefix(op, operand) if is_word(op) => write!(f, "{} {}", op, operand),
+            Self::Prefix(op, operand) => write!(f, "{}{}", op, operand),
+            Self::Binary(op, lhs, rhs) => write!(f, "({} {} {})", lhs, op, rhs),
+        }
+    }
+}
+
+/// Function that applies a prefix operator
+type PrefixFn<T> = fn(T) -> T;
+
+/// Function that applies a binary operator
+type BinaryFn<T> = fn(T, T) -> T;
+
+struct Operator<F> {
+    symbol: &'static str,
+    precedence: u8,
+    associativity: Associativity,
+    apply: F,
+}
+
+/// Table of operators used to parse and evaluate expressions
+///
+/// Operators with a higher precedence are applied first. Symbols can either be punctuation
+/// (e.g. `+`, `<<`) or words (e.g. `AND`) which must be separated from their operands.
+///
+/// ```
+/// # use utils::expr::{Associativity, Grammar};
+/// let grammar = Grammar::<u32>::new()
+///     .binary("+", 1, Associativity::Left, |a, b| a + b)
+///     .binary("*", 2, Associativity::Left, |a, b| a * b);
+/// let expr = grammar.parse("x * (2 + 3) + 4").unwrap();
+/// assert_eq!(grammar.evaluate(&expr, &mut |_| Some(10)), Ok(54));
+/// ```
+pub struct Grammar<T> {
+    prefix: Vec<Operator<PrefixFn<T>>>,
+    binary: Vec<Operator<BinaryFn<T>>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token<'a> {
+    Operand(&'a str),
+    Operator(&'static str),
+    Open,
+    Close,
+}
+
+impl<T> Grammar<T> {
+    /// Creates a new `Grammar` without any operators
+    pub const fn new() -> Self {
+        Self {
+            prefix: Vec::new(),
+            binary: Vec::new(),
+        }
+    }
+
+    /// Adds a prefix (unary) operator such as `-x` or `NOT x`
+    #[must_use]
+    pub fn prefix(mut self, symbol: &'static str, precedence: u8, apply: PrefixFn<T>) -> Self {
+        self.prefix.push(Operator {
+            symbol,
+            precedence,
+            associativity: Associativity::Right,
+            apply,
+        });
+        self
+    }
+
+    /// Adds a binary operator such as `a + b` or `a AND b`
+    #[must_use]
+    pub fn binary(
+        mut self,
+        symbol: &'static str,
+        precedence: u8,
+        associativity: Associativity,
+        apply: BinaryFn<T>,
+    ) -> Self {
+        self.binary.push(Operator {
+            symbol,
+            precedence,
+            associativity,
+            apply,
+        });
+        self
+    }
+
+    /// Evaluates an expression, using `lookup` to get the values of variables
+    ///
+    /// Fails with `ExprError::UnknownOperator` if the expression uses an operator that isn't
+    /// part of this grammar (e.g. because it was parsed by a different one)
+    pub fn evaluate(
+        &self,
+        expr: &Expr<T>,
+        lookup: &mut impl FnMut(&str) -> Option<T>,
+    ) -> Result<T, ExprError>
+    where
+        T: Clone,
+    {
+        match expr {
+            Expr::Value(value) => Ok(value.clone()),
+            Expr::Variable(name) => {
+                lookup(name).ok_or_else(|| ExprError::UnknownVariable { name: name.clone() })
+            }
+            Expr::Prefix(op, operand) => {
+                let operand = self.evaluate(operand, lookup)?;
+                let operator = find_operator(&self.prefix, op)?;
+                Ok((operator.apply)(operand))
+            }
+            Expr::Binary(op, lhs, rhs) => {
+                let lhs = self.evaluate(lhs, lookup)?;
+                let rhs = self.evaluate(rhs, lookup)?;
+                let operator = find_operator(&self.binary, op)?;
+                Ok((operator.apply)(lhs, rhs))
+            }
+        }
+    }
+
+    /// Parses an expression
+    pub fn parse(&self, s: &str) -> Result<Expr<T>, ExprError>
+    where
+        T: FromStr,
+    {
+        let tokens = self.tokenize(s)?;
+        let mut tokens = tokens.into_iter().peekable();
+        let expr = self.parse_expr(&mut tokens, 0)?;
+        match tokens.next() {
+            None => Ok(expr),
+            Some((position, token)) => Err(unexpected(position, &token)),
+        }
+    }
+
+    /// Parses an expression until reaching an operator with a lower precedence than `min_precedence`
+    fn parse_expr<'a>(
+        &self,
+        tokens: &mut std::iter::Peekable<impl Iterator<Item = (usize, Token<'a>)>>,
+        min_precedence: u16,
+    ) -> Result<Expr<T>, ExprError>
+    where
+        T: FromStr,
+    {
+        let (position, token) = tokens.next().ok_or(ExprError::UnexpectedEnd)?;
+        let mut lhs = match token {
+            Token::Operand(s) => {
+                if let Ok(value) = s.parse() {
+                    Expr::Value(value)
+                } else if s.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+                    Expr::Variable(s.to_string())
+                } else {
+                    return Err(unexpected(position, &token));
+                }
+            }
+            Token::Open => {
+                let expr = self.parse_expr(tokens, 0)?;
+                match tokens.next() {
+                    Some((_, Token::Close)) => expr,
+                    Some((position, token)) => return Err(unexpected(position, &token)),
+                    None => return Err(ExprError::UnexpectedEnd),
+                }
+            }
+            Token::Operator(op) => match self.prefix.iter().find(|o| o.symbol == op) {
+                Some(operator) => {
+                    let operand = self.parse_expr(tokens, u16::from(operator.precedence))?;
+                    Expr::Prefix(op, Box::new(operand))
+                }
+                None => return Err(unexpected(position, &token)),
+            },
+            Token::Close => return Err(unexpected(position, &token)),
+        };
+        // Keep applying binary operators while they bind at least as tightly as the caller's
+        while let Some((position, Token::Operator(op))) = tokens.peek() {
+            let operator = self
+                .binary
+                .iter()
+                .find(|o| o.symbol == *op)
+                .ok_or_else(|| unexpected(*position, &Token::Operator(op)))?;
+            let precedence = u16::from(operator.precedence);
+            if precedence < min_precedence {
+                break;
+            }
+            tokens.next();
+            // Left associative operators stop the right hand side at the same precedence
+            // (widened so that this still works for operators with a precedence of `u8::MAX`)
+            let next_precedence = match operator.associativity {
+                Associativity::Left => precedence + 1,
+                Associativity::Right => precedence,
+            };
+            let rhs = self.parse_expr(tokens, next_precedence)?;
+            lhs = Expr::Binary(operator.symbol, Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    /// Splits an expression into (position, token)
+    fn tokenize<'a>(&self, s: &'a str) -> Result<Vec<(usize, Token<'a>)>, ExprError> {
+        // Try to match longer symbols first (e.g. so that `<<` isn't read as two `<`)
+        let mut symbols = self
+            .prefix
+            .iter()
+            .map(|o| o.symbol)
+            .chain(self.binary.iter().map(|o| o.symbol))
+            .collect::<Vec<_>>();
+        symbols.sort_by_key(|symbol| std::cmp::Reverse(symbol.len()));
+        let mut tokens = Vec::new();
+        let mut position = 0;
+        while let Some(c) = s[position..].chars().next() {
+            let rest = &s[position..];
+            let word_len = rest.find(|c: char| !is_word_char(c)).unwrap_or(rest.len());
+            let (token, len) = if c.is_whitespace() {
+                position += c.len_utf8();
+                continue;
+            } else if c == '(' {
+                (Token::Open, 1)
+            } else if c == ')' {
+                (Token::Close, 1)
+            } else if let Some(symbol) = symbols.iter().find(|symbol| {
+                // Word operators must match a whole word
+                rest.starts_with(*symbol) && (!is_word(symbol) || symbol.len() == word_len)
+            }) {
+                (Token::Operator(symbol), symbol.len())
+            } else if word_len > 0 {
+                (Token::Operand(&rest[..word_len]), word_len)
+            } else {
+                return Err(ExprError::UnexpectedChar { c, position });
+            };
+            tokens.push((position, token));
+            position += len;
+        }
+        Ok(tokens)
+    }
+}
+
+impl<T> Default for Grammar<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Checks whether a char can be part of a word (a number, variable or word operator)
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Checks whether an operator symbol is a word (e.g. `AND`) rather than punctuation (e.g. `&`)
+fn is_word(symbol: &str) -> bool {
+    symbol.chars().all(is_word_char)
+}
+
+/// Finds the operator with the given symbol
+fn find_operator<'a, F>(
+    operators: &'a [Operator<F>],
+    symbol: &str,
+) -> Result<&'a Operator<F>, ExprError> {
+    operators
+        .iter()
+        .find(|o| o.symbol == symbol)
+        .ok_or_else(|| ExprError::UnknownOperator {
+            symbol: symbol.to_string(),
+        })
+}
+
+/// Creates the error for a token that isn't valid where it was found
+fn unexpected(position: usize, token: &Token) -> ExprError {
+    let token = match token {
+        Token::Operand(s) => (*s).to_string(),
+        Token::Operator(symbol) => (*symbol).to_string(),
+        Token::Open => "(".to_string(),
+        Token::Close => ")".to_string(),
+    };
+    ExprError::UnexpectedToken { token, position }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn arithmetic() -> Grammar<i64> {
+        Grammar::<i64>::new()
+            .binary("+", 1, Associativity::Left, |a, b| a + b)
+            .binary("-", 1, Associativity::Left, |a, b| a - b)
+            .binary("*", 2, Associativity::Left, |a, b| a * b)
+            .binary("^", 3, Associativity::Right, |a, b| a.pow(b as u32))
+            .prefix("-", 4, |a| -a)
+    }
+
+    fn eval(grammar: &Grammar<i64>, s: &str) -> i64 {
+        grammar
+            .evaluate(&grammar.parse(s).unwrap(), &mut |_| None)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_precedence() {
+        let grammar = arithmetic();
+        assert_eq!(eval(&grammar, "1 + 2 * 3"), 7);
+        assert_eq!(eval(&grammar, "(1 + 2) * 3"), 9);
+        assert_eq!(eval(&grammar, "10 - 4 - 3"), 3);
+        assert_eq!(eval(&grammar, "2 ^ 3 ^ 2"), 512);
+        assert_eq!(eval(&grammar, "-2 ^ 2"), 4);
+        assert_eq!(eval(&grammar, "--3*-(2+1)"), -9);
+        assert_eq!(
+            grammar.parse("2 ^ 3 ^ 2").unwrap().to_string(),
+            "(2 ^ (3 ^ 2))"
+        );
+    }
+
+    #[test]
+    fn test_2020_day18_rules() {
+        // Part 1: Same precedence, part 2: + before *
+        let same = Grammar::<u64>::new()
+            .binary("+", 1, Associativity::Left, |a, b| a + b)
+            .binary("*", 1, Associativity::Left, |a, b| a * b);
+        let add_first = Grammar::<u64>::new()
+            .binary("+", 2, Associativity::Left, |a, b| a + b)
+            .binary("*", 1, Associativity::Left, |a, b| a * b);
+        let s = "((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2";
+        assert_eq!(
+            same.evaluate(&same.parse(s).unwrap(), &mut |_| None),
+            Ok(13632)
+        );
+        assert_eq!(
+            add_first.evaluate(&add_first.parse(s).unwrap(), &mut |_| None),
+            Ok(23340)
+        );
+    }
+
+    #[test]
+    fn test_variables_and_words() {
+        let grammar = Grammar::<u16>::new()
+            .binary("AND", 1, Associativity::Left, |a, b| a & b)
+            .binary("OR", 1, Associativity::Left, |a, b| a | b)
+            .binary("LSHIFT", 1, Associativity::Left, |a, b| a << b)
+            .prefix("NOT", 2, |a| !a);
+        let expr = grammar.parse("NOT x AND ORx LSHIFT 2").unwrap();
+        assert_eq!(expr.variables(), vec!["x", "ORx"]);
+        assert_eq!(expr.to_string(), "((NOT x AND ORx) LSHIFT 2)");
+        let mut lookup = |name: &str| match name {
+            "x" => Some(123),
+            "ORx" => Some(456),
+            _ => None,
+        };
+        assert_eq!(grammar.evaluate(&expr, &mut lookup), Ok(1536));
+        assert_eq!(
+            grammar.evaluate(&grammar.parse("y").unwrap(), &mut lookup),
+            Err(ExprError::UnknownVariable {
+                name: "y".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_max_precedence() {
+        let grammar = Grammar::<i64>::new()
+            .binary("-", u8::MAX, Associativity::Left, |a, b| a - b)
+            .binary("^", u8::MAX, Associativity::Right, |a, b| a.pow(b as u32));
+        assert_eq!(eval(&grammar, "10 - 4 - 3"), 3);
+        assert_eq!(eval(&grammar, "2 ^ 3 ^ 2"), 512);
+    }
+
+    #[test]
+    fn test_other_grammar() {
+        // Expressions that use operators the grammar doesn't know about can't be evaluated
+        let expr = arithmetic().parse("-1 * 2").unwrap();
+        let only_minus = Grammar::<i64>::new().prefix("-", 1, |a| -a);
+        assert_eq!(
+            only_minus.evaluate(&expr, &mut |_| None),
+            Err(ExprError::UnknownOperator {
+                symbol: "*".to_string()
+            })
+        );
+        let only_times = Grammar::<i64>::new().binary("*", 1, Associativity::Left, |a, b| a * b);
+        assert_eq!(
+            only_times.evaluate(&expr, &mut |_| None),
+            Err(ExprError::UnknownOperator {
+                symbol: "-".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        let grammar = arithmetic();
+        assert_eq!(grammar.parse("1 +"), Err(ExprError::UnexpectedEnd));
+        assert_eq!(grammar.parse("(1 + 2"), Err(ExprError::UnexpectedEnd));
+        assert_eq!(
+            grammar.parse("1 + 2)"),
+            Err(ExprError::UnexpectedToken {
+                token: ")".to_string(),
+                position: 5
+            })
+        );
+        assert_eq!(
+            grammar.parse("1 2"),
+            Err(ExprError::UnexpectedToken {
+                token: "2".to_string(),
+                position: 2
+            })
+        );
+        assert_eq!(
+            grammar.parse("* 2"),
+            Err(ExprError::UnexpectedToken {
+                token: "*".to_string(),
+                position: 0
+            })
+        );
+        assert_eq!(
+            grammar.parse("1 % 2"),
+            Err(ExprError::UnexpectedChar {
+                c: '%',
+                position: 2
+            })
+        );
+        assert_eq!(
+            grammar.parse("12ab"),
+            Err(ExprError::UnexpectedToken {
+                token: "12ab".to_string(),
+                position: 0
+            })
+        );
+    }
+}
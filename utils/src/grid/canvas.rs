@@ -0,0 +1,297 @@
+use crate::grid::{MapGrid, Pos, VecGrid};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// Grid of chars rendered from a `VecGrid` or `MapGrid` for display or export
+///
+/// The canvas keeps track of the position of its top left corner so that overlays can
+/// be drawn using the same positions as the grid it was rendered from
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Canvas {
+    origin: Pos,
+    rows: Vec<Vec<char>>,
+}
+
+impl Canvas {
+    /// Renders a `VecGrid` using `cell` to convert each value to a char
+    pub fn from_vec_grid<V: Clone + Copy>(grid: &VecGrid<V>, cell: impl Fn(&V) -> char) -> Self {
+        Self {
+            origin: Pos::new(0, 0),
+            rows: grid
+                .rows()
+                .map(|row| row.iter().map(&cell).collect())
+                .collect(),
+        }
+    }
+
+    /// Renders a `MapGrid` using `cell` to convert each value to a char
+    ///
+    /// The canvas covers the bounds of all positions with values, with `empty`
+    /// used for any positions without values
+    pub fn from_map_grid<V: Clone>(
+        grid: &MapGrid<V>,
+        empty: char,
+        cell: impl Fn(&V) -> char,
+    ) -> Self {
+        let mut keys = grid.keys();
+        let (mut min, mut max) = match keys.next() {
+            Some(&first) => (first, first),
+            None => {
+                return Self {
+                    origin: Pos::new(0, 0),
+                    rows: Vec::new(),
+                }
+            }
+        };
+        for pos in keys {
+            min = Pos {
+                x: min.x.min(pos.x),
+                y: min.y.min(pos.y),
+            };
+            max = Pos {
+                x: max.x.max(pos.x),
+                y: max.y.max(pos.y),
+            };
+        }
+        let mut canvas = Self {
+            origin: min,
+            rows: vec![vec![empty; (max.x - min.x + 1) as usize]; (max.y - min.y + 1) as usize],
+        };
+        for (pos, value) in grid.iter() {
+            canvas.draw(*pos, cell(value));
+        }
+        canvas
+    }
+
+    /// Gets the width of the canvas
+    pub fn width(&self) -> usize {
+        self.rows.first().map_or(0, Vec::len)
+    }
+
+    /// Gets the height of the canvas
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Gets the char drawn at the supplied grid position or None if it is outside of the canvas
+    pub fn get(&self, pos: Pos) -> Option<char> {
+        let (x, y) = self.offset(pos)?;
+        self.rows.get(y)?.get(x).copied()
+    }
+
+    /// Draws `c` at the supplied grid position (ignoring positions outside of the canvas)
+    pub fn draw(&mut self, pos: Pos, c: char) {
+        if let Some((x, y)) = self.offset(pos) {
+            if let Some(cell) = self.rows.get_mut(y).and_then(|row| row.get_mut(x)) {
+                *cell = c;
+            }
+        }
+    }
+
+    /// Returns the canvas with `c` drawn over all of the supplied grid positions
+    /// (e.g. to highlight a path)
+    #[must_use]
+    pub fn overlay(mut self, positions: impl IntoIterator<Item = Pos>, c: char) -> Self {
+        for pos in positions {
+            self.draw(pos, c);
+        }
+        self
+    }
+
+    /// Writes the canvas as a plain (ASCII) PBM image, using `lit` to decide which chars are black
+    pub fn write_pbm(&self, mut w: impl Write, lit: impl Fn(char) -> bool) -> io::Result<()> {
+        writeln!(w, "P1")?;
+        writeln!(w, "{} {}", self.width(), self.height())?;
+        self.write_pixels(w, |c| u8::from(lit(c)).to_string())
+    }
+
+    /// Writes the canvas as a plain (ASCII) PGM image, using `shade` to get the grey level of each char
+    /// (where 0 is black and 255 is white)
+    pub fn write_pgm(&self, mut w: impl Write, shade: impl Fn(char) -> u8) -> io::Result<()> {
+        writeln!(w, "P2")?;
+        writeln!(w, "{} {}", self.width(), self.height())?;
+        writeln!(w, "255")?;
+        self.write_pixels(w, |c| shade(c).to_string())
+    }
+
+    /// Writes the canvas as a plain (ASCII) PPM image, using `colour` to get the (r, g, b) of each char
+    pub fn write_ppm(
+        &self,
+        mut w: impl Write,
+        colour: impl Fn(char) -> (u8, u8, u8),
+    ) -> io::Result<()> {
+        writeln!(w, "P3")?;
+        writeln!(w, "{} {}", self.width(), self.height())?;
+        writeln!(w, "255")?;
+        self.write_pixels(w, |c| {
+            let (r, g, b) = colour(c);
+            format!("{} {} {}", r, g, b)
+        })
+    }
+
+    /// Saves the canvas as a plain PPM image file
+    pub fn save_ppm(
+        &self,
+        path: impl AsRef<Path>,
+        colour: impl Fn(char) -> (u8, u8, u8),
+    ) -> io::Result<()> {
+        self.write_ppm(BufWriter::new(File::create(path)?), colour)
+    }
+
+    /// Converts a grid position into a (column, row) offset into the canvas
+    fn offset(&self, pos: Pos) -> Option<(usize, usize)> {
+        let shifted = pos - self.origin;
+        Some((
+            usize::try_from(shifted.x).ok()?,
+            usize::try_from(shifted.y).ok()?,
+        ))
+    }
+
+    /// Writes one line of space separated pixel values per row
+    fn write_pixels(&self, mut w: impl Write, pixel: impl Fn(char) -> String) -> io::Result<()> {
+        for row in &self.rows {
+            let values = row.iter().map(|&c| pixel(c)).collect::<Vec<_>>();
+            writeln!(w, "{}", values.join(" "))?;
+        }
+        w.flush()
+    }
+}
+
+impl fmt::Display for Canvas {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in &self.rows {
+            writeln!(f, "{}", row.iter().collect::<String>())?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes a sequence of canvases (e.g. each step of a simulation) for inspecting offline
+pub struct Frames<W: Write> {
+    writer: W,
+    count: usize,
+}
+
+impl<W: Write> Frames<W> {
+    /// Creates a new sequence of text frames that will be written to `writer`
+    pub const fn new(writer: W) -> Self {
+        Self { writer, count: 0 }
+    }
+
+    /// Writes the next frame as text preceded by a header line with the frame number
+    pub fn push(&mut self, canvas: &Canvas) -> io::Result<()> {
+        writeln!(self.writer, "Frame {}:", self.count)?;
+        writeln!(self.writer, "{}", canvas)?;
+        self.count += 1;
+        self.writer.flush()
+    }
+
+    /// Gets the number of frames written so far
+    pub const fn len(&self) -> usize {
+        self.count
+    }
+
+    /// Checks whether no frames have been written yet
+    pub const fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Consumes the `Frames`, returning the underlying writer
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Saves each canvas as a numbered PPM image in `dir` (`frame_0000.ppm`, `frame_0001.ppm` etc)
+///
+/// Returns the number of frames saved
+pub fn save_ppm_frames<'a>(
+    dir: impl AsRef<Path>,
+    canvases: impl IntoIterator<Item = &'a Canvas>,
+    colour: impl Fn(char) -> (u8, u8, u8),
+) -> io::Result<usize> {
+    std::fs::create_dir_all(&dir)?;
+    let mut count = 0;
+    for canvas in canvases {
+        canvas.save_ppm(
+            dir.as_ref().join(format!("frame_{:04}.ppm", count)),
+            &colour,
+        )?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vec_grid_display_and_overlay() {
+        let grid: VecGrid<u8> = "123\n456".parse().unwrap();
+        let canvas = grid.render(|&v| if v % 2 == 0 { '#' } else { '.' });
+        assert_eq!(canvas.to_string(), ".#.\n#.#\n");
+        assert_eq!(grid.to_string(), "123\n456\n");
+        let path = canvas.overlay([Pos::new(0, 0), Pos::new(1, 0), Pos::new(5, 5)], 'O');
+        assert_eq!(path.to_string(), "OO.\n#.#\n");
+        assert_eq!(path.get(Pos::new(2, 1)), Some('#'));
+        assert_eq!(path.get(Pos::from((-1, 0))), None);
+    }
+
+    #[test]
+    fn test_map_grid_bounds() {
+        let grid: MapGrid<bool> = [(Pos::from((-2, -1)), true), (Pos::from((1, 0)), false)]
+            .into_iter()
+            .collect();
+        let canvas = grid.render(' ', |&v| if v { '#' } else { '.' });
+        assert_eq!((canvas.width(), canvas.height()), (4, 2));
+        assert_eq!(canvas.to_string(), "#   \n   .\n");
+        assert_eq!(
+            Canvas::from_map_grid(&MapGrid::<bool>::new(), ' ', |_| '#').to_string(),
+            ""
+        );
+    }
+
+    #[test]
+    fn test_images() {
+        let grid: VecGrid<char> = "#.\n.#".parse().unwrap();
+        let canvas = Canvas::from_vec_grid(&grid, |&c| c);
+        let mut pbm = Vec::new();
+        canvas.write_pbm(&mut pbm, |c| c == '#').unwrap();
+        assert_eq!(String::from_utf8(pbm).unwrap(), "P1\n2 2\n1 0\n0 1\n");
+        let mut pgm = Vec::new();
+        canvas
+            .write_pgm(&mut pgm, |c| if c == '#' { 0 } else { 255 })
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(pgm).unwrap(),
+            "P2\n2 2\n255\n0 255\n255 0\n"
+        );
+        let mut ppm = Vec::new();
+        canvas
+            .write_ppm(&mut ppm, |c| if c == '#' { (255, 0, 0) } else { (0, 0, 0) })
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(ppm).unwrap(),
+            "P3\n2 2\n255\n255 0 0 0 0 0\n0 0 0 255 0 0\n"
+        );
+    }
+
+    #[test]
+    fn test_frames() {
+        let grid: VecGrid<char> = "ab".parse().unwrap();
+        let mut frames = Frames::new(Vec::new());
+        assert!(frames.is_empty());
+        frames.push(&Canvas::from_vec_grid(&grid, |&c| c)).unwrap();
+        frames
+            .push(&Canvas::from_vec_grid(&grid.mirror(), |&c| c))
+            .unwrap();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(
+            String::from_utf8(frames.into_inner()).unwrap(),
+            "Frame 0:\nab\n\nFrame 1:\nba\n\n"
+        );
+    }
+}
@@ -0,0 +1,431 @@
+//! A module for working with hexagonal grids
+//!
+//! Positions are stored using axial coordinates (q, r) with the third cube
+//! coordinate (s) derived as needed so that q + r + s = 0.
+//! See `https://www.redblobgames.com/grids/hexagons/` for background.
+
+use std::collections::HashMap;
+use std::iter::FromIterator;
+use std::ops::{Add, AddAssign, Mul, Sub};
+use std::str::FromStr;
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ParseHexDirectionError {
+    #[error("invalid direction: '{0}' is not a valid hex direction")]
+    InvalidDirection(String),
+}
+
+/// Axial offsets for each of the 6 neighbours, in anti-clockwise order starting from +q
+const AXIAL_OFFSETS: [(isize, isize); 6] = [(1, 0), (1, -1), (0, -1), (-1, 0), (-1, 1), (0, 1)];
+
+/// Position on a hex grid using axial coordinates
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, PartialOrd, Ord, Default)]
+pub struct Hex {
+    pub q: isize,
+    pub r: isize,
+}
+
+/// Common behaviour for the directions that can be moved in on a hex grid
+pub trait HexDirection: Copy + IntoEnumIterator {
+    /// Gets the axial offset of moving one step in this direction
+    fn offset(self) -> Hex;
+
+    /// Parses a list of directions that have been written without any separators (e.g. "nwwswee")
+    fn parse_list(s: &str) -> Result<Vec<Self>, ParseHexDirectionError>
+    where
+        Self: FromStr<Err = ParseHexDirectionError>,
+    {
+        let mut directions = Vec::new();
+        let mut remaining = s.trim();
+        while !remaining.is_empty() {
+            // Directions are either one or two chars, try the longer one first
+            let two = remaining.get(..2).and_then(|d| d.parse().ok());
+            if let Some(direction) = two {
+                directions.push(direction);
+                remaining = &remaining[2..];
+            } else {
+                let end = remaining.chars().next().map_or(0, char::len_utf8);
+                directions.push(remaining[..end].parse()?);
+                remaining = &remaining[end..];
+            }
+        }
+        Ok(directions)
+    }
+}
+
+/// Directions on a grid of flat topped hexes (which have neighbours to the north and south)
+#[derive(Debug, Copy, Clone, Eq, PartialEq, EnumIter, Hash)]
+pub enum FlatDirection {
+    North,
+    NorthEast,
+    SouthEast,
+    South,
+    SouthWest,
+    NorthWest,
+}
+
+/// Directions on a grid of pointy topped hexes (which have neighbours to the east and west)
+#[derive(Debug, Copy, Clone, Eq, PartialEq, EnumIter, Hash)]
+pub enum PointyDirection {
+    East,
+    SouthEast,
+    SouthWest,
+    West,
+    NorthWest,
+    NorthEast,
+}
+
+impl HexDirection for FlatDirection {
+    fn offset(self) -> Hex {
+        use FlatDirection::{North, NorthEast, NorthWest, South, SouthEast, SouthWest};
+        match self {
+            North => Hex::new(0, -1),
+            NorthEast => Hex::new(1, -1),
+            SouthEast => Hex::new(1, 0),
+            South => Hex::new(0, 1),
+            SouthWest => Hex::new(-1, 1),
+            NorthWest => Hex::new(-1, 0),
+        }
+    }
+}
+
+impl HexDirection for PointyDirection {
+    fn offset(self) -> Hex {
+        use PointyDirection::{East, NorthEast, NorthWest, SouthEast, SouthWest, West};
+        match self {
+            East => Hex::new(1, 0),
+            SouthEast => Hex::new(0, 1),
+            SouthWest => Hex::new(-1, 1),
+            West => Hex::new(-1, 0),
+            NorthWest => Hex::new(0, -1),
+            NorthEast => Hex::new(1, -1),
+        }
+    }
+}
+
+impl FromStr for FlatDirection {
+    type Err = ParseHexDirectionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "n" | "north" => Ok(Self::North),
+            "ne" | "northeast" => Ok(Self::NorthEast),
+            "se" | "southeast" => Ok(Self::SouthEast),
+            "s" | "south" => Ok(Self::South),
+            "sw" | "southwest" => Ok(Self::SouthWest),
+            "nw" | "northwest" => Ok(Self::NorthWest),
+            _ => Err(ParseHexDirectionError::InvalidDirection(s.to_owned())),
+        }
+    }
+}
+
+impl FromStr for PointyDirection {
+    type Err = ParseHexDirectionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "e" | "east" => Ok(Self::East),
+            "se" | "southeast" => Ok(Self::SouthEast),
+            "sw" | "southwest" => Ok(Self::SouthWest),
+            "w" | "west" => Ok(Self::West),
+            "nw" | "northwest" => Ok(Self::NorthWest),
+            "ne" | "northeast" => Ok(Self::NorthEast),
+            _ => Err(ParseHexDirectionError::InvalidDirection(s.to_owned())),
+        }
+    }
+}
+
+impl Hex {
+    /// Creates a new position from axial coordinates
+    pub const fn new(q: isize, r: isize) -> Self {
+        Self { q, r }
+    }
+
+    /// Creates a new position from cube coordinates
+    ///
+    /// # Panics
+    ///
+    /// If the coordinates don't sum to 0
+    pub fn from_cube(q: isize, r: isize, s: isize) -> Self {
+        assert_eq!(q + r + s, 0, "cube coordinates must sum to 0");
+        Self { q, r }
+    }
+
+    /// Gets the position at the centre of the grid
+    pub const fn origin() -> Self {
+        Self::new(0, 0)
+    }
+
+    /// Gets the derived third cube coordinate
+    pub const fn s(&self) -> isize {
+        -self.q - self.r
+    }
+
+    /// Gets the position as (q, r, s) cube coordinates
+    pub const fn cube(&self) -> (isize, isize, isize) {
+        (self.q, self.r, self.s())
+    }
+
+    /// Gets the next position if we headed in the supplied direction
+    pub fn next(&self, direction: impl HexDirection) -> Self {
+        *self + direction.offset()
+    }
+
+    /// Gets the next position if we headed distance places in the supplied direction
+    pub fn next_by(&self, direction: impl HexDirection, distance: isize) -> Self {
+        *self + direction.offset() * distance
+    }
+
+    /// Gets position of the 6 neighbours
+    pub fn neighbours(self) -> impl Iterator<Item = Self> {
+        AXIAL_OFFSETS
+            .iter()
+            .map(move |&(q, r)| self + Self::new(q, r))
+    }
+
+    /// Calculates the number of steps needed to get to another position
+    pub fn distance(&self, from: &Self) -> usize {
+        (*self - *from).distance_origin()
+    }
+
+    /// Calculates the number of steps needed to get to the origin
+    pub fn distance_origin(&self) -> usize {
+        (self.q.unsigned_abs() + self.r.unsigned_abs() + self.s().unsigned_abs()) / 2
+    }
+
+    /// Gets all positions that are exactly `radius` steps away
+    ///
+    /// A radius of 0 just returns this position
+    pub fn ring(self, radius: usize) -> impl Iterator<Item = Self> {
+        let (q, r) = AXIAL_OFFSETS[4];
+        let start = self + Self::new(q, r) * radius as isize;
+        let sides = if radius == 0 { 1 } else { 6 };
+        (0..sides)
+            .flat_map(move |side| std::iter::repeat_n(side, radius.max(1)))
+            .scan(start, move |pos, side| {
+                let current = *pos;
+                let (q, r) = AXIAL_OFFSETS[side];
+                *pos += Self::new(q, r);
+                Some(current)
+            })
+    }
+
+    /// Gets all positions that are up to and including `radius` steps away,
+    /// starting from this position and spiralling outwards one ring at a time
+    pub fn spiral(self, radius: usize) -> impl Iterator<Item = Self> {
+        (0..=radius).flat_map(move |r| self.ring(r))
+    }
+}
+
+impl Add<Hex> for Hex {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(self.q + other.q, self.r + other.r)
+    }
+}
+
+impl AddAssign<Hex> for Hex {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl Sub<Hex> for Hex {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.q - other.q, self.r - other.r)
+    }
+}
+
+impl Mul<isize> for Hex {
+    type Output = Self;
+
+    fn mul(self, rhs: isize) -> Self {
+        Self::new(self.q * rhs, self.r * rhs)
+    }
+}
+
+impl Add<FlatDirection> for Hex {
+    type Output = Self;
+
+    fn add(self, direction: FlatDirection) -> Self {
+        self.next(direction)
+    }
+}
+
+impl Add<PointyDirection> for Hex {
+    type Output = Self;
+
+    fn add(self, direction: PointyDirection) -> Self {
+        self.next(direction)
+    }
+}
+
+/// Hex grid that uses a `HashMap` to store data of a unknown and non-fixed size
+#[derive(Clone, Debug)]
+pub struct HexGrid<V> {
+    data: HashMap<Hex, V>,
+}
+
+impl<V> Default for HexGrid<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> FromIterator<(Hex, V)> for HexGrid<V> {
+    /// Creates a new `HexGrid` from an iterator
+    fn from_iter<I: IntoIterator<Item = (Hex, V)>>(iter: I) -> Self {
+        Self {
+            data: HashMap::from_iter(iter),
+        }
+    }
+}
+
+impl<V> HexGrid<V> {
+    /// Creates a new empty `HexGrid`
+    pub fn new() -> Self {
+        Self {
+            data: HashMap::new(),
+        }
+    }
+
+    /// An iterator visiting all key-value pairs in arbitrary order.
+    pub fn iter(&self) -> std::collections::hash_map::Iter<'_, Hex, V> {
+        self.data.iter()
+    }
+
+    /// An iterator visiting all keys in arbitrary order.
+    pub fn keys(&self) -> std::collections::hash_map::Keys<'_, Hex, V> {
+        self.data.keys()
+    }
+
+    /// An iterator visiting all values in arbitrary order.
+    pub fn values(&self) -> std::collections::hash_map::Values<'_, Hex, V> {
+        self.data.values()
+    }
+
+    /// Gets the number of positions with values
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Checks whether there are no positions with values
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Inserts the element into the supplied position
+    pub fn insert(&mut self, pos: Hex, value: V) -> Option<V> {
+        self.data.insert(pos, value)
+    }
+
+    /// Removes a key from the map, returning the value at the key if the key was previously in the map.
+    pub fn remove(&mut self, pos: &Hex) -> Option<V> {
+        self.data.remove(pos)
+    }
+
+    /// Returns a reference to the value at the position
+    pub fn get(&self, pos: &Hex) -> Option<&V> {
+        self.data.get(pos)
+    }
+
+    /// Returns a mutable reference to the value at the position
+    pub fn get_mut(&mut self, pos: &Hex) -> Option<&mut V> {
+        self.data.get_mut(pos)
+    }
+
+    /// Gets the given position's corresponding entry in the map for in-place manipulation
+    pub fn entry(&mut self, key: Hex) -> std::collections::hash_map::Entry<'_, Hex, V> {
+        self.data.entry(key)
+    }
+
+    /// Gets the values of the 6 neighbours to the supplied position
+    pub fn neighbours(&self, pos: Hex) -> impl Iterator<Item = Option<&V>> + '_ {
+        pos.neighbours().map(move |neighbour| self.get(&neighbour))
+    }
+
+    /// Gets the position and values of the 6 neighbours to the supplied position
+    pub fn neighbours_ex(&self, pos: Hex) -> impl Iterator<Item = (Hex, Option<&V>)> + '_ {
+        pos.neighbours()
+            .map(move |neighbour| (neighbour, self.get(&neighbour)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn walk<D: HexDirection>(directions: &[D]) -> Hex {
+        directions.iter().fold(Hex::origin(), |pos, d| pos.next(*d))
+    }
+
+    #[test]
+    fn test_flat_distance() {
+        // Examples from 2017 day 11
+        let parse = |s: &str| {
+            s.split(',')
+                .map(|d| d.parse::<FlatDirection>().unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(walk(&parse("ne,ne,ne")).distance_origin(), 3);
+        assert_eq!(walk(&parse("ne,ne,sw,sw")).distance_origin(), 0);
+        assert_eq!(walk(&parse("ne,ne,s,s")).distance_origin(), 2);
+        assert_eq!(walk(&parse("se,sw,se,sw,sw")).distance_origin(), 3);
+        assert_eq!(
+            "x".parse::<FlatDirection>(),
+            Err(ParseHexDirectionError::InvalidDirection("x".to_owned()))
+        );
+    }
+
+    #[test]
+    fn test_pointy_parse_list() {
+        // Examples from 2020 day 24
+        let esew = PointyDirection::parse_list("esew").unwrap();
+        assert_eq!(walk(&esew), Hex::origin().next(PointyDirection::SouthEast));
+        assert_eq!(
+            walk(&PointyDirection::parse_list("nwwswee").unwrap()),
+            Hex::origin()
+        );
+        assert_eq!(
+            PointyDirection::parse_list("ex"),
+            Err(ParseHexDirectionError::InvalidDirection("x".to_owned()))
+        );
+    }
+
+    #[test]
+    fn test_all_directions_are_neighbours() {
+        let neighbours = Hex::origin().neighbours().collect::<HashSet<_>>();
+        assert!(FlatDirection::iter().all(|d| neighbours.contains(&d.offset())));
+        assert!(PointyDirection::iter().all(|d| neighbours.contains(&d.offset())));
+    }
+
+    #[test]
+    fn test_ring_and_spiral() {
+        let centre = Hex::from_cube(2, -3, 1);
+        assert_eq!(centre.ring(0).collect::<Vec<_>>(), vec![centre]);
+        for radius in 1..5 {
+            let ring = centre.ring(radius).collect::<HashSet<_>>();
+            assert_eq!(ring.len(), 6 * radius);
+            assert!(ring.iter().all(|h| h.distance(&centre) == radius));
+        }
+        let spiral = centre.spiral(3).collect::<Vec<_>>();
+        assert_eq!(spiral.len(), 1 + 3 * 3 * 4);
+        assert_eq!(spiral[0], centre);
+    }
+
+    #[test]
+    fn test_hex_grid() {
+        let grid: HexGrid<bool> = Hex::origin().ring(1).map(|h| (h, true)).collect();
+        assert_eq!(grid.len(), 6);
+        assert_eq!(grid.neighbours(Hex::origin()).flatten().count(), 6);
+        let east = Hex::origin() + PointyDirection::East;
+        assert_eq!(grid.neighbours(east).flatten().count(), 2);
+    }
+}
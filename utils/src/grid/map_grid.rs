@@ -1,7 +1,9 @@
+use crate::grid::parse::{parse_cells, Markers, ParseGridError};
+use crate::grid::Canvas;
 use crate::grid::Direction;
 use crate::grid::Pos;
 use std::collections::HashMap;
-use std::fmt::Display;
+use std::fmt::{Debug, Display};
 use std::iter::FromIterator;
 use std::str::FromStr;
 
@@ -45,6 +47,39 @@ impl FromStr for MapGrid<char> {
     }
 }
 
+impl<V> MapGrid<V>
+where
+    V: TryFrom<char>,
+    V::Error: Debug,
+{
+    /// Creates a new `MapGrid` from an ASCII grid, converting each char using `TryFrom<char>`
+    pub fn try_parse(s: &str) -> Result<Self, ParseGridError<V::Error>> {
+        Self::try_parse_with_markers(s, |_| None::<((), V)>).map(|(grid, _)| grid)
+    }
+
+    /// Creates a new `MapGrid` from an ASCII grid, extracting marker cells (e.g. start
+    /// positions or units) into a separate list of positions
+    ///
+    /// `marker` should return the marker and the value to store in the grid in its place
+    /// for marker chars, and None for all other chars which are converted using `TryFrom<char>`
+    pub fn try_parse_with_markers<M>(
+        s: &str,
+        marker: impl FnMut(char) -> Option<(M, V)>,
+    ) -> Result<(Self, Markers<M>), ParseGridError<V::Error>> {
+        let (cells, markers) = parse_cells(s, marker)?;
+        let data = cells
+            .into_iter()
+            .enumerate()
+            .flat_map(|(y, row)| {
+                row.into_iter()
+                    .enumerate()
+                    .map(move |(x, value)| (Pos::new(x, y), value))
+            })
+            .collect();
+        Ok((Self { data }, markers))
+    }
+}
+
 impl<V: Clone> MapGrid<V> {
     /// Creates a new empty `MapGrid`
     pub fn new() -> Self {
@@ -133,6 +168,14 @@ impl<V: Clone> MapGrid<V> {
         grid
     }
 
+    /// Renders the grid to a `Canvas` using `cell` to convert each value to a char
+    ///
+    /// The canvas covers the bounds of all positions with values, with `empty`
+    /// used for any positions without values
+    pub fn render(&self, empty: char, cell: impl Fn(&V) -> char) -> Canvas {
+        Canvas::from_map_grid(self, empty, cell)
+    }
+
     /// Prints the grid to the console
     pub fn print(&self, default: V)
     where
@@ -146,3 +189,31 @@ impl<V: Clone> MapGrid<V> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_try_parse_with_markers() {
+        // Units from 2018 day 15 are extracted and replaced with open floor
+        let (cave, units) = MapGrid::<Direction>::try_parse_with_markers("UGR\nEDL", |c| match c {
+            'G' | 'E' => Some((c, Direction::Up)),
+            _ => None,
+        })
+        .unwrap();
+        assert_eq!(units, [(Pos::new(1, 0), 'G'), (Pos::new(0, 1), 'E')]);
+        assert_eq!(cave.get(&Pos::new(1, 0)), Some(&Direction::Up));
+        assert_eq!(cave.get(&Pos::new(2, 1)), Some(&Direction::Left));
+        assert_eq!(cave.keys().count(), 6);
+        assert!(matches!(
+            MapGrid::<Direction>::try_parse("UD\nLX"),
+            Err(ParseGridError::InvalidCell {
+                row: 1,
+                column: 1,
+                c: 'X',
+                ..
+            })
+        ));
+    }
+}
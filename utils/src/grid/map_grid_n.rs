@@ -0,0 +1,154 @@
+use crate::grid::{MapGrid, PosN};
+use std::collections::HashMap;
+use std::iter::FromIterator;
+
+/// Grid with `D` dimensions that uses a `HashMap` to store data of a unknown and non-fixed size
+#[derive(Clone, Debug)]
+pub struct MapGridN<V, const D: usize> {
+    data: HashMap<PosN<D>, V>,
+}
+
+impl<V, const D: usize> Default for MapGridN<V, D> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V, const D: usize> FromIterator<(PosN<D>, V)> for MapGridN<V, D> {
+    /// Creates a new `MapGridN` from an iterator
+    fn from_iter<I: IntoIterator<Item = (PosN<D>, V)>>(iter: I) -> Self {
+        Self {
+            data: HashMap::from_iter(iter),
+        }
+    }
+}
+
+impl<V: Clone, const D: usize> From<&MapGrid<V>> for MapGridN<V, D> {
+    /// Creates a new `MapGridN` from a 2D `MapGrid`, with all other coordinates set to 0
+    fn from(grid: &MapGrid<V>) -> Self {
+        grid.iter()
+            .map(|(pos, value)| (PosN::from_pos(*pos), value.clone()))
+            .collect()
+    }
+}
+
+impl<V, const D: usize> MapGridN<V, D> {
+    /// Creates a new empty `MapGridN`
+    pub fn new() -> Self {
+        Self {
+            data: HashMap::new(),
+        }
+    }
+
+    /// An iterator visiting all key-value pairs in arbitrary order.
+    pub fn iter(&self) -> std::collections::hash_map::Iter<'_, PosN<D>, V> {
+        self.data.iter()
+    }
+
+    /// An iterator visiting all keys in arbitrary order.
+    pub fn keys(&self) -> std::collections::hash_map::Keys<'_, PosN<D>, V> {
+        self.data.keys()
+    }
+
+    /// An iterator visiting all values in arbitrary order.
+    pub fn values(&self) -> std::collections::hash_map::Values<'_, PosN<D>, V> {
+        self.data.values()
+    }
+
+    /// Gets the number of positions with values
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Checks whether there are no positions with values
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Checks whether the supplied position has a value
+    pub fn contains(&self, pos: &PosN<D>) -> bool {
+        self.data.contains_key(pos)
+    }
+
+    /// Inserts the element into the supplied position
+    pub fn insert(&mut self, pos: PosN<D>, value: V) -> Option<V> {
+        self.data.insert(pos, value)
+    }
+
+    /// Removes a key from the map, returning the value at the key if the key was previously in the map.
+    pub fn remove(&mut self, pos: &PosN<D>) -> Option<V> {
+        self.data.remove(pos)
+    }
+
+    /// Returns a reference to the value at the position
+    pub fn get(&self, pos: &PosN<D>) -> Option<&V> {
+        self.data.get(pos)
+    }
+
+    /// Returns a mutable reference to the value at the position
+    pub fn get_mut(&mut self, pos: &PosN<D>) -> Option<&mut V> {
+        self.data.get_mut(pos)
+    }
+
+    /// Gets the given position's corresponding entry in the map for in-place manipulation
+    pub fn entry(&mut self, key: PosN<D>) -> std::collections::hash_map::Entry<'_, PosN<D>, V> {
+        self.data.entry(key)
+    }
+
+    /// Gets the values of the 3^D - 1 neighbours to the supplied position
+    pub fn neighbours(&self, pos: PosN<D>) -> impl Iterator<Item = Option<&V>> + '_ {
+        pos.neighbours().map(move |neighbour| self.get(&neighbour))
+    }
+
+    /// Gets the position and values of the 3^D - 1 neighbours to the supplied position
+    pub fn neighbours_ex(&self, pos: PosN<D>) -> impl Iterator<Item = (PosN<D>, Option<&V>)> + '_ {
+        pos.neighbours()
+            .map(move |neighbour| (neighbour, self.get(&neighbour)))
+    }
+
+    /// Gets the minimum and maximum corners of the box that bounds all positions
+    /// with values, or `None` if the grid is empty
+    pub fn bounds(&self) -> Option<(PosN<D>, PosN<D>)> {
+        let mut keys = self.data.keys();
+        let first = *keys.next()?;
+        Some(keys.fold((first, first), |(min, max), &pos| {
+            (min.min(pos), max.max(pos))
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::{Pos3, Pos4};
+
+    #[test]
+    fn test_from_map_grid() {
+        let grid: MapGrid<char> = ".#.\n..#\n###".parse().unwrap();
+        let grid3: MapGridN<char, 3> = MapGridN::from(&grid);
+        assert_eq!(grid3.len(), 9);
+        assert_eq!(grid3.get(&PosN([1, 0, 0])), Some(&'#'));
+        assert_eq!(grid3.get(&PosN([1, 0, 1])), None);
+        assert_eq!(grid3.bounds(), Some((Pos3::origin(), Pos3::new([2, 2, 0]))));
+        assert_eq!(
+            grid3
+                .neighbours(Pos3::new([1, 1, 0]))
+                .flatten()
+                .filter(|&&c| c == '#')
+                .count(),
+            5
+        );
+    }
+
+    #[test]
+    fn test_neighbours_ex() {
+        let grid: MapGridN<bool, 4> = [(Pos4::origin(), true)].into_iter().collect();
+        let lit = grid
+            .neighbours_ex(Pos4::new([1, 1, 1, 1]))
+            .filter(|(_, v)| v.is_some())
+            .map(|(p, _)| p)
+            .collect::<Vec<_>>();
+        assert_eq!(lit, vec![Pos4::origin()]);
+        assert!(MapGridN::<bool, 4>::new().bounds().is_none());
+    }
+}
@@ -1,11 +1,23 @@
 //! A module for working with grids of both known and unknown size
 
+pub use self::canvas::{save_ppm_frames, Canvas, Frames};
 pub use self::direction::Direction;
 pub use self::map_grid::MapGrid;
+pub use self::map_grid_n::MapGridN;
+pub use self::parse::{Markers, ParseGridError};
 pub use self::pos::Pos;
-pub use self::vec_grid::VecGrid;
+pub use self::pos_n::{ParsePosNError, Pos3, Pos4, PosN};
+pub use self::vec_grid::{VecGrid, Wrap};
+pub use self::vec_grid_view::VecGridView;
 
+pub mod hex;
+
+mod canvas;
 mod direction;
 mod map_grid;
+mod map_grid_n;
+mod parse;
 mod pos;
+mod pos_n;
 mod vec_grid;
+mod vec_grid_view;
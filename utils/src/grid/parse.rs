@@ -0,0 +1,60 @@
+use crate::grid::Pos;
+use std::fmt::Debug;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ParseGridError<E: Debug> {
+    #[error(
+        "invalid cell: '{c}' at row {row}, column {column} could not be converted ({error:?})"
+    )]
+    InvalidCell {
+        row: usize,
+        column: usize,
+        c: char,
+        error: E,
+    },
+}
+
+/// Positions of marker cells found while parsing a grid
+pub type Markers<M> = Vec<(Pos, M)>;
+
+/// Rows of cells and the markers parsed from a grid
+type Parsed<V, M> = (Vec<Vec<V>>, Markers<M>);
+
+/// Parses an ASCII grid into rows of cells, converting each char using `TryFrom<char>`
+///
+/// Chars for which `marker` returns a value are recorded along with their position
+/// and replaced by the cell value returned with the marker
+pub(crate) fn parse_cells<V, M, F>(
+    s: &str,
+    mut marker: F,
+) -> Result<Parsed<V, M>, ParseGridError<V::Error>>
+where
+    V: TryFrom<char>,
+    V::Error: Debug,
+    F: FnMut(char) -> Option<(M, V)>,
+{
+    let mut markers = Vec::new();
+    let cells = s
+        .lines()
+        .enumerate()
+        .map(|(row, line)| {
+            line.chars()
+                .enumerate()
+                .map(|(column, c)| {
+                    if let Some((m, value)) = marker(c) {
+                        markers.push((Pos::new(column, row), m));
+                        return Ok(value);
+                    }
+                    V::try_from(c).map_err(|error| ParseGridError::InvalidCell {
+                        row,
+                        column,
+                        c,
+                        error,
+                    })
+                })
+                .collect()
+        })
+        .collect::<Result<_, _>>()?;
+    Ok((cells, markers))
+}
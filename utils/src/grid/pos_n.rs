@@ -0,0 +1,273 @@
+use crate::grid::Pos;
+use std::ops::{Add, AddAssign, Index, IndexMut, Mul, Neg, Sub, SubAssign};
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ParsePosNError {
+    #[error("invalid format: '{0}' does not have {1} comma separated values")]
+    InvalidDimensions(String, usize),
+    #[error("invalid number: '{0}' is not a valid number")]
+    InvalidNumber(String),
+}
+
+/// Position with `D` dimensions for referencing values in a `MapGridN`
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub struct PosN<const D: usize>(pub [isize; D]);
+
+/// (x, y, z) position
+pub type Pos3 = PosN<3>;
+
+/// (x, y, z, w) position
+pub type Pos4 = PosN<4>;
+
+impl<const D: usize> Default for PosN<D> {
+    fn default() -> Self {
+        Self::origin()
+    }
+}
+
+impl<const D: usize> FromStr for PosN<D> {
+    type Err = ParsePosNError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Split 'x,y,z,...' string, parse as a number and build a PosN from it
+        let values = s
+            .split(',')
+            .map(|n| {
+                n.trim()
+                    .parse()
+                    .map_err(|_| ParsePosNError::InvalidNumber(n.to_owned()))
+            })
+            .collect::<Result<Vec<isize>, _>>()?;
+        values
+            .try_into()
+            .map(Self)
+            .map_err(|_| ParsePosNError::InvalidDimensions(s.to_owned(), D))
+    }
+}
+
+impl<const D: usize> From<[isize; D]> for PosN<D> {
+    fn from(item: [isize; D]) -> Self {
+        Self(item)
+    }
+}
+
+impl From<Pos> for PosN<2> {
+    fn from(item: Pos) -> Self {
+        Self([item.x, item.y])
+    }
+}
+
+impl From<PosN<2>> for Pos {
+    fn from(item: PosN<2>) -> Self {
+        Self {
+            x: item.0[0],
+            y: item.0[1],
+        }
+    }
+}
+
+impl<const D: usize> PosN<D> {
+    /// Creates a new position from the supplied coordinates
+    pub const fn new(coords: [isize; D]) -> Self {
+        Self(coords)
+    }
+
+    /// Gets the position with all coordinates set to 0
+    pub const fn origin() -> Self {
+        Self([0; D])
+    }
+
+    /// Creates a new position from a 2D `Pos`, with all other coordinates set to 0
+    pub fn from_pos(pos: Pos) -> Self {
+        assert!(D >= 2, "need at least 2 dimensions to hold a Pos");
+        let mut coords = [0; D];
+        coords[0] = pos.x;
+        coords[1] = pos.y;
+        Self(coords)
+    }
+
+    /// Gets the coordinates of the position
+    pub const fn coords(&self) -> &[isize; D] {
+        &self.0
+    }
+
+    /// Gets position of the 3^D - 1 neighbours (including diagonals)
+    pub fn neighbours(self) -> impl Iterator<Item = Self> {
+        (0..3usize.pow(D as u32)).filter_map(move |mut i| {
+            // Treat i as a D digit base 3 number, with each digit giving an offset of -1, 0 or 1
+            let mut next = self;
+            for coord in &mut next.0 {
+                *coord += (i % 3) as isize - 1;
+                i /= 3;
+            }
+            (next != self).then_some(next)
+        })
+    }
+
+    /// Gets position of the 2 * D neighbours that differ along a single axis
+    pub fn neighbours_axes(self) -> impl Iterator<Item = Self> {
+        (0..D).flat_map(move |axis| {
+            [-1, 1].into_iter().map(move |shift| {
+                let mut next = self;
+                next.0[axis] += shift;
+                next
+            })
+        })
+    }
+
+    /// Calculates the manhattan distance from another point
+    pub fn manhattan_distance(&self, from: &Self) -> usize {
+        self.0
+            .iter()
+            .zip(from.0.iter())
+            .map(|(a, b)| a.abs_diff(*b))
+            .sum()
+    }
+
+    /// Calculates the manhattan distance from the origin
+    pub fn manhattan_distance_origin(&self) -> usize {
+        self.0.iter().map(|c| c.unsigned_abs()).sum()
+    }
+
+    /// Returns the position with each coordinate replaced by the minimum from either position
+    pub fn min(self, other: Self) -> Self {
+        let mut result = self;
+        for (a, b) in result.0.iter_mut().zip(other.0) {
+            *a = (*a).min(b);
+        }
+        result
+    }
+
+    /// Returns the position with each coordinate replaced by the maximum from either position
+    pub fn max(self, other: Self) -> Self {
+        let mut result = self;
+        for (a, b) in result.0.iter_mut().zip(other.0) {
+            *a = (*a).max(b);
+        }
+        result
+    }
+}
+
+impl<const D: usize> Index<usize> for PosN<D> {
+    type Output = isize;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.0[index]
+    }
+}
+
+impl<const D: usize> IndexMut<usize> for PosN<D> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.0[index]
+    }
+}
+
+impl<const D: usize> Add<PosN<D>> for PosN<D> {
+    type Output = Self;
+
+    fn add(mut self, other: Self) -> Self {
+        self += other;
+        self
+    }
+}
+
+impl<const D: usize> AddAssign<PosN<D>> for PosN<D> {
+    fn add_assign(&mut self, other: Self) {
+        for (a, b) in self.0.iter_mut().zip(other.0) {
+            *a += b;
+        }
+    }
+}
+
+impl<const D: usize> Sub<PosN<D>> for PosN<D> {
+    type Output = Self;
+
+    fn sub(mut self, other: Self) -> Self {
+        self -= other;
+        self
+    }
+}
+
+impl<const D: usize> SubAssign<PosN<D>> for PosN<D> {
+    fn sub_assign(&mut self, other: Self) {
+        for (a, b) in self.0.iter_mut().zip(other.0) {
+            *a -= b;
+        }
+    }
+}
+
+impl<const D: usize> Mul<isize> for PosN<D> {
+    type Output = Self;
+
+    fn mul(mut self, rhs: isize) -> Self {
+        for a in &mut self.0 {
+            *a *= rhs;
+        }
+        self
+    }
+}
+
+impl<const D: usize> Neg for PosN<D> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        self * -1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!("1,-2,3".parse::<Pos3>(), Ok(PosN([1, -2, 3])));
+        assert_eq!("1, 2, 3, 4".parse::<Pos4>(), Ok(PosN([1, 2, 3, 4])));
+        assert_eq!(
+            "1,2".parse::<Pos3>(),
+            Err(ParsePosNError::InvalidDimensions("1,2".to_owned(), 3))
+        );
+        assert_eq!(
+            "1,n,3".parse::<Pos3>(),
+            Err(ParsePosNError::InvalidNumber("n".to_owned()))
+        );
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let a = Pos3::new([1, 2, 3]);
+        let b = Pos3::new([-4, 5, 0]);
+        assert_eq!(a + b, PosN([-3, 7, 3]));
+        assert_eq!(a - b, PosN([5, -3, 3]));
+        assert_eq!(a * 2, PosN([2, 4, 6]));
+        assert_eq!(-a, PosN([-1, -2, -3]));
+        assert_eq!(a.min(b), PosN([-4, 2, 0]));
+        assert_eq!(a.max(b), PosN([1, 5, 3]));
+        assert_eq!(a.manhattan_distance(&b), 11);
+        assert_eq!(b.manhattan_distance_origin(), 9);
+        assert_eq!(a[2], 3);
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let pos = Pos3::new([1, 1, 1]);
+        let neighbours = pos.neighbours().collect::<Vec<_>>();
+        assert_eq!(neighbours.len(), 26);
+        assert!(!neighbours.contains(&pos));
+        assert!(neighbours.iter().all(|n| n.manhattan_distance(&pos) <= 3));
+        assert_eq!(Pos4::origin().neighbours().count(), 80);
+        assert_eq!(pos.neighbours_axes().count(), 6);
+        assert!(pos
+            .neighbours_axes()
+            .all(|n| n.manhattan_distance(&pos) == 1));
+    }
+
+    #[test]
+    fn test_pos_conversion() {
+        let pos = Pos { x: 3, y: -1 };
+        assert_eq!(Pos::from(PosN::from(pos)), pos);
+        assert_eq!(Pos4::from_pos(pos), PosN([3, -1, 0, 0]));
+    }
+}
@@ -1,8 +1,10 @@
 use crate::grid::direction::Direction;
+use crate::grid::parse::{parse_cells, Markers, ParseGridError};
 use crate::grid::pos::Pos;
+use crate::grid::{Canvas, VecGridView};
 use itertools::Itertools;
-use std::fmt::Display;
-use std::ops::{Index, IndexMut};
+use std::fmt::{Debug, Display};
+use std::ops::{Index, IndexMut, Range};
 use std::{convert::Infallible, str::FromStr};
 use strum::IntoEnumIterator;
 
@@ -10,6 +12,21 @@ use strum::IntoEnumIterator;
 #[derive(Clone)]
 pub struct VecGrid<V> {
     data: Vec<Vec<V>>,
+    wrap: Wrap,
+}
+
+/// Which edges of a `VecGrid` wrap around to the opposite side
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum Wrap {
+    /// Positions beyond any edge are outside of the grid
+    #[default]
+    None,
+    /// The left and right edges wrap around
+    Horizontal,
+    /// The top and bottom edges wrap around
+    Vertical,
+    /// All edges wrap around (a torus)
+    Both,
 }
 
 /// Iterator over (position, value) tuples
@@ -58,16 +75,16 @@ impl<V: Clone + Copy> Default for VecGrid<V> {
 
 impl<V: Clone + Copy> VecGrid<V> {
     /// Gets the element at the supplied position or None if it is outside of bounds
+    ///
+    /// Positions beyond wrapping edges are wrapped back into the grid
     pub fn get(&self, pos: Pos) -> Option<V> {
-        if self.contains(pos) {
-            Some(self.data[pos.y as usize][pos.x as usize])
-        } else {
-            None // Outside of bounds
-        }
+        let pos = self.wrapped(pos);
+        self.contains(pos)
+            .then(|| self.data[pos.y as usize][pos.x as usize])
     }
 
     pub fn insert(&mut self, pos: Pos, value: V) {
-        self.data[pos.y as usize][pos.x as usize] = value;
+        self[pos] = value;
     }
 
     pub fn values(&self) -> impl Iterator<Item = &V> + '_ {
@@ -80,12 +97,14 @@ impl<V: Clone + Copy> VecGrid<V> {
     }
 
     /// Gets the direction, pos and values of the 4 neighbours to the supplied position
+    ///
+    /// Positions beyond wrapping edges are wrapped back into the grid
     pub fn neighbours_ex(
         &self,
         pos: Pos,
     ) -> impl Iterator<Item = (Direction, Pos, Option<V>)> + '_ {
         Direction::iter().map(move |d| {
-            let next = pos.next(d);
+            let next = self.wrapped(pos.next(d));
             (d, next, self.get(next))
         })
     }
@@ -96,9 +115,65 @@ impl<V: Clone + Copy> VecGrid<V> {
     }
 
     /// Gets the position and values of the 8 neighbours to the supplied position
+    ///
+    /// Positions beyond wrapping edges are wrapped back into the grid
     pub fn neighbours8_ex(&self, pos: Pos) -> impl Iterator<Item = (Pos, Option<V>)> + '_ {
-        pos.neighbours8()
-            .map(move |neighbour| (neighbour, self.get(neighbour)))
+        pos.neighbours8().map(move |neighbour| {
+            let neighbour = self.wrapped(neighbour);
+            (neighbour, self.get(neighbour))
+        })
+    }
+
+    /// Gets an iterator over each row of the grid
+    pub fn rows(&self) -> impl Iterator<Item = &[V]> + '_ {
+        self.data.iter().map(Vec::as_slice)
+    }
+
+    /// Gets an iterator over each column of the grid
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = V> + '_> + '_ {
+        (0..self.width()).map(move |x| self.column(x))
+    }
+
+    /// Gets the values in the row at `y`
+    pub fn row(&self, y: usize) -> &[V] {
+        &self.data[y]
+    }
+
+    /// Gets the values in the column at `x` from top to bottom
+    pub fn column(&self, x: usize) -> impl Iterator<Item = V> + '_ {
+        self.data.iter().map(move |row| row[x])
+    }
+
+    /// Gets the positions and values heading away from `pos` in the supplied direction
+    /// (not including `pos` itself) until the edge of the grid is reached
+    ///
+    /// Rays never wrap, even if the grid does
+    pub fn ray(&self, pos: Pos, direction: Direction) -> impl Iterator<Item = (Pos, V)> + '_ {
+        let step = pos.next(direction) - pos;
+        self.ray_by(pos, (step.x, step.y))
+    }
+
+    /// Gets the positions and values heading away from `pos` by repeatedly adding `step`
+    /// (not including `pos` itself) until the edge of the grid is reached
+    ///
+    /// Rays never wrap, even if the grid does, and are empty if `step` is (0, 0)
+    pub fn ray_by(&self, pos: Pos, step: (isize, isize)) -> impl Iterator<Item = (Pos, V)> + '_ {
+        let first = (step != (0, 0)).then(|| pos + step);
+        std::iter::successors(first, move |&next| Some(next + step))
+            .take_while(move |&next| self.contains(next))
+            .map(move |next| (next, self.data[next.y as usize][next.x as usize]))
+    }
+
+    /// Gets the 8 rays (including diagonals) heading away from `pos`, in the same
+    /// order as `neighbours8`
+    pub fn rays8(
+        &self,
+        pos: Pos,
+    ) -> impl Iterator<Item = impl Iterator<Item = (Pos, V)> + '_> + '_ {
+        pos.neighbours8().map(move |neighbour| {
+            let step = neighbour - pos;
+            self.ray_by(pos, (step.x, step.y))
+        })
     }
 
     /// Returns a new `VecGrid` rotated 90 degrees clockwise
@@ -110,7 +185,10 @@ impl<V: Clone + Copy> VecGrid<V> {
                 result[y][x] = self.data[self.width() - x - 1][y];
             }
         }
-        result.into()
+        Self {
+            data: result,
+            wrap: self.wrap,
+        }
     }
 
     /// Returns a new `VecGrid` with all rows reversed
@@ -121,24 +199,166 @@ impl<V: Clone + Copy> VecGrid<V> {
                 result[y][x] = self.data[y][self.width() - x - 1];
             }
         }
-        result.into()
+        Self {
+            data: result,
+            wrap: self.wrap,
+        }
+    }
+
+    /// Returns a new `VecGrid` with rows and columns swapped
+    pub fn transpose(&self) -> Self {
+        let data = (0..self.width())
+            .map(|x| self.data.iter().map(|row| row[x]).collect())
+            .collect();
+        Self {
+            data,
+            wrap: self.wrap,
+        }
+    }
+
+    /// Returns a new `VecGrid` with the order of the rows reversed
+    pub fn flip(&self) -> Self {
+        Self {
+            data: self.data.iter().rev().cloned().collect(),
+            wrap: self.wrap,
+        }
+    }
+
+    /// Returns a new `VecGrid` rotated 90 degrees clockwise `turns` times
+    ///
+    /// Unlike `rotate` this supports grids that aren't square and negative
+    /// values of `turns` to rotate anti-clockwise
+    pub fn rotate_by(&self, turns: isize) -> Self {
+        match turns.rem_euclid(4) {
+            0 => self.clone(),
+            1 => self.transpose().mirror(),
+            2 => self.mirror().flip(),
+            _ => self.transpose().flip(),
+        }
+    }
+
+    /// Returns an iterator over all 8 orientations of the grid (the 4 rotations
+    /// followed by the 4 rotations of the mirrored grid), starting with the grid as is
+    pub fn orientations(&self) -> impl Iterator<Item = Self> + '_ {
+        let mirrored = self.mirror();
+        (0..4)
+            .map(move |turns| self.rotate_by(turns))
+            .chain((0..4).map(move |turns| mirrored.rotate_by(turns)))
+    }
+
+    /// Gets a borrowed view of the sub-grid covering the supplied columns and rows
+    ///
+    /// # Panics
+    ///
+    /// If the ranges extend beyond the grid
+    pub fn view(&self, x: Range<usize>, y: Range<usize>) -> VecGridView<'_, V> {
+        assert!(x.end <= self.width() && y.end <= self.height());
+        VecGridView::new(self, x, y)
+    }
+
+    /// Splits the grid into rows of tiles each `width` x `height` in size
+    ///
+    /// # Panics
+    ///
+    /// If the grid cannot be split exactly into tiles of the supplied size
+    pub fn split(&self, width: usize, height: usize) -> Vec<Vec<Self>> {
+        assert!(self.width().is_multiple_of(width) && self.height().is_multiple_of(height));
+        (0..self.height())
+            .step_by(height)
+            .map(|y| {
+                (0..self.width())
+                    .step_by(width)
+                    .map(|x| self.view(x..x + width, y..y + height).to_grid())
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Stitches rows of tiles back together into a single grid
+    ///
+    /// # Panics
+    ///
+    /// If tiles in the same row have different heights, or rows have different widths
+    pub fn stitch(tiles: &[Vec<Self>]) -> Self {
+        let data = tiles
+            .iter()
+            .flat_map(|row| {
+                let height = row.first().map_or(0, Self::height);
+                assert!(row.iter().all(|tile| tile.height() == height));
+                (0..height).map(move |y| {
+                    row.iter()
+                        .flat_map(|tile| tile.data[y].iter().copied())
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect::<Vec<_>>();
+        assert!(data.iter().all(|row| row.len() == data[0].len()));
+        data.into()
+    }
+}
+
+impl<V> VecGrid<V> {
+    /// Wraps `pos` along any wrapping edges, leaving it unchanged if none wrap
+    /// (or the grid is empty)
+    #[inline]
+    fn wrapped(&self, pos: Pos) -> Pos {
+        if self.wrap == Wrap::None {
+            return pos;
+        }
+        let width = self.data.first().map_or(0, Vec::len) as isize;
+        let height = self.data.len() as isize;
+        if width == 0 || height == 0 {
+            return pos;
+        }
+        match self.wrap {
+            Wrap::None => pos,
+            Wrap::Horizontal => Pos {
+                x: pos.x.rem_euclid(width),
+                y: pos.y,
+            },
+            Wrap::Vertical => Pos {
+                x: pos.x,
+                y: pos.y.rem_euclid(height),
+            },
+            Wrap::Both => Pos {
+                x: pos.x.rem_euclid(width),
+                y: pos.y.rem_euclid(height),
+            },
+        }
     }
 }
 
 impl<V> Index<Pos> for VecGrid<V> {
     type Output = V;
 
+    /// Gets the value at the position, which is wrapped back into the grid
+    /// if it is beyond a wrapping edge
     fn index(&self, index: Pos) -> &Self::Output {
+        let index = self.wrapped(index);
         &self.data[index.y as usize][index.x as usize]
     }
 }
 
 impl<V> IndexMut<Pos> for VecGrid<V> {
     fn index_mut(&mut self, index: Pos) -> &mut Self::Output {
+        let index = self.wrapped(index);
         &mut self.data[index.y as usize][index.x as usize]
     }
 }
 
+impl<V: Display> Display for VecGrid<V> {
+    /// Writes the grid with each row on a new line
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in &self.data {
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
 impl FromStr for VecGrid<char> {
     type Err = Infallible;
 
@@ -149,6 +369,7 @@ impl FromStr for VecGrid<char> {
                 .lines()
                 .map(|line| line.chars().collect::<Vec<_>>())
                 .collect(),
+            wrap: Wrap::None,
         })
     }
 }
@@ -167,6 +388,7 @@ impl FromStr for VecGrid<u8> {
                         .collect::<Vec<_>>()
                 })
                 .collect(),
+            wrap: Wrap::None,
         })
     }
 }
@@ -174,7 +396,10 @@ impl FromStr for VecGrid<u8> {
 impl<V> From<Vec<Vec<V>>> for VecGrid<V> {
     /// Creates a new `VecGrid` from an existing nested vector
     fn from(data: Vec<Vec<V>>) -> Self {
-        Self { data }
+        Self {
+            data,
+            wrap: Wrap::None,
+        }
     }
 }
 
@@ -186,23 +411,90 @@ impl From<Vec<&str>> for VecGrid<char> {
                 .iter()
                 .map(|line| line.chars().collect::<Vec<_>>())
                 .collect(),
+            wrap: Wrap::None,
         }
     }
 }
 
+impl<V> VecGrid<V>
+where
+    V: TryFrom<char>,
+    V::Error: Debug,
+{
+    /// Creates a new `VecGrid` from an ASCII grid, converting each char using `TryFrom<char>`
+    pub fn try_parse(s: &str) -> Result<Self, ParseGridError<V::Error>> {
+        Self::try_parse_with_markers(s, |_| None::<((), V)>).map(|(grid, _)| grid)
+    }
+
+    /// Creates a new `VecGrid` from an ASCII grid, extracting marker cells (e.g. start
+    /// positions or units) into a separate list of positions
+    ///
+    /// `marker` should return the marker and the value to store in the grid in its place
+    /// for marker chars, and None for all other chars which are converted using `TryFrom<char>`
+    pub fn try_parse_with_markers<M>(
+        s: &str,
+        marker: impl FnMut(char) -> Option<(M, V)>,
+    ) -> Result<(Self, Markers<M>), ParseGridError<V::Error>> {
+        let (data, markers) = parse_cells(s, marker)?;
+        Ok((
+            Self {
+                data,
+                wrap: Wrap::None,
+            },
+            markers,
+        ))
+    }
+}
+
 impl<V: Clone> VecGrid<V> {
     /// Creates a new empty `VecGrid`
     pub fn new() -> Self {
-        Self { data: Vec::new() }
+        Self {
+            data: Vec::new(),
+            wrap: Wrap::None,
+        }
     }
 
     /// Creates a new empty `VecGrid` of the specified size
     pub fn new_sized(default: V, width: usize, height: usize) -> Self {
         Self {
             data: vec![vec![default; width]; height],
+            wrap: Wrap::None,
         }
     }
 
+    /// Returns the grid with the supplied edges set to wrap around
+    pub fn with_wrap(mut self, wrap: Wrap) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    /// Sets which edges of the grid wrap around
+    pub fn set_wrap(&mut self, wrap: Wrap) {
+        self.wrap = wrap;
+    }
+
+    /// Gets which edges of the grid wrap around
+    pub const fn wrap(&self) -> Wrap {
+        self.wrap
+    }
+
+    /// Wraps the supplied position back into the grid along any wrapping edges
+    ///
+    /// Returns None if the position is still outside of the grid
+    pub fn wrap_pos(&self, pos: Pos) -> Option<Pos> {
+        let pos = self.wrapped(pos);
+        self.contains(pos).then_some(pos)
+    }
+
+    /// Gets the next position if we headed in the supplied direction, wrapping
+    /// along any wrapping edges
+    ///
+    /// Returns None if the next position is outside of the grid
+    pub fn next_pos(&self, pos: Pos, direction: Direction) -> Option<Pos> {
+        self.wrap_pos(pos.next(direction))
+    }
+
     /// Returns an iterator with all indexes to valid items in the grid
     pub fn indexes(&'_ self) -> impl Iterator<Item = Pos> {
         (0..self.width())
@@ -227,7 +519,7 @@ impl<V: Clone> VecGrid<V> {
 
     /// Sets the value at the specified position
     pub fn set(&mut self, pos: Pos, value: V) {
-        self.data[pos.y as usize][pos.x as usize] = value;
+        self[pos] = value;
     }
 
     /// Checks whether the supplied position exists within the grid
@@ -248,9 +540,282 @@ impl<V: Clone> VecGrid<V> {
         }
     }
 
+    /// Renders the grid to a `Canvas` using `cell` to convert each value to a char
+    pub fn render(&self, cell: impl Fn(&V) -> char) -> Canvas
+    where
+        V: Copy,
+    {
+        Canvas::from_vec_grid(self, cell)
+    }
+
     /// Gets as a reference to the value at the supplied position without
     /// performing any bounds checking before hand
     fn get_ref_no_check(&self, pos: Pos) -> &V {
         &self.data[pos.y as usize][pos.x as usize]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    static SAMPLE: &str = indoc! {"
+    abc
+    def
+    "};
+
+    static SQUARE: &str = indoc! {"
+    .#.
+    ..#
+    ###
+    "};
+
+    fn grid(s: &str) -> VecGrid<char> {
+        s.parse().unwrap()
+    }
+
+    fn to_string(grid: &VecGrid<char>) -> String {
+        grid.data
+            .iter()
+            .map(|row| row.iter().collect::<String>() + "\n")
+            .collect()
+    }
+
+    #[test]
+    fn test_rotate_by() {
+        let square = grid(SQUARE);
+        assert_eq!(to_string(&square.rotate_by(1)), to_string(&square.rotate()));
+        assert_eq!(
+            to_string(&square.rotate_by(-1)),
+            to_string(&square.rotate_by(3))
+        );
+        assert_eq!(to_string(&grid(SAMPLE).rotate_by(1)), "da\neb\nfc\n");
+        assert_eq!(to_string(&grid(SAMPLE).rotate_by(2)), "fed\ncba\n");
+        assert_eq!(to_string(&grid(SAMPLE).rotate_by(3)), "cf\nbe\nad\n");
+        assert_eq!(to_string(&grid(SAMPLE).transpose()), "ad\nbe\ncf\n");
+        assert_eq!(to_string(&grid(SAMPLE).flip()), "def\nabc\n");
+    }
+
+    #[test]
+    fn test_orientations() {
+        let orientations = grid(SQUARE)
+            .orientations()
+            .map(|g| to_string(&g))
+            .collect::<std::collections::HashSet<_>>();
+        assert_eq!(orientations.len(), 8);
+        // Transposing is one of the orientations
+        assert!(orientations.contains(&to_string(&grid(SQUARE).transpose())));
+    }
+
+    #[test]
+    fn test_view() {
+        let square = grid(SQUARE);
+        let view = square.view(1..3, 1..3);
+        assert_eq!((view.width(), view.height()), (2, 2));
+        assert_eq!(view[Pos::new(1, 0)], '#');
+        assert_eq!(view.get(Pos::new(2, 0)), None);
+        assert_eq!(view.iter().filter(|(_, &c)| c == '#').count(), 3);
+        assert_eq!(to_string(&view.to_grid()), ".#\n##\n");
+    }
+
+    #[test]
+    fn test_split_and_stitch() {
+        let big = grid("#..#\n....\n....\n#..#\n");
+        let tiles = big.split(2, 2);
+        assert_eq!(tiles.len(), 2);
+        assert_eq!(to_string(&tiles[1][1]), "..\n.#\n");
+        // Fractal art style - replace each tile then stitch back together
+        let grown = tiles
+            .iter()
+            .map(|row| row.iter().map(|tile| tile.rotate_by(2)).collect())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            to_string(&VecGrid::stitch(&grown)),
+            "....\n.##.\n.##.\n....\n"
+        );
+        assert_eq!(to_string(&VecGrid::stitch(&tiles)), to_string(&big));
+    }
+
+    #[test]
+    fn test_rows_and_columns() {
+        let grid = grid(SAMPLE);
+        assert_eq!(
+            grid.rows().collect::<Vec<_>>(),
+            [['a', 'b', 'c'], ['d', 'e', 'f']]
+        );
+        assert_eq!(grid.row(1), ['d', 'e', 'f']);
+        assert_eq!(grid.column(2).collect::<String>(), "cf");
+        assert_eq!(
+            grid.columns()
+                .map(|col| col.collect::<String>())
+                .collect::<Vec<_>>(),
+            ["ad", "be", "cf"]
+        );
+    }
+
+    #[test]
+    fn test_rays() {
+        // Example from 2022 day 8, the middle 5 in the 2nd row is visible from the top and right
+        let trees: VecGrid<u8> = "30373\n25512\n65332\n33549\n35390".parse().unwrap();
+        let pos = Pos::new(2, 1);
+        let visible = Direction::all()
+            .filter(|&d| trees.ray(pos, d).all(|(_, h)| h < trees[pos]))
+            .collect::<Vec<_>>();
+        assert_eq!(visible, [Direction::Up, Direction::Right]);
+        assert_eq!(
+            trees.ray(pos, Direction::Left).collect::<Vec<_>>(),
+            [(Pos::new(1, 1), 5), (Pos::new(0, 1), 2)]
+        );
+        assert_eq!(trees.ray(Pos::new(0, 0), Direction::Up).count(), 0);
+        // Example from 2020 day 11, the empty seat can see 8 occupied seats
+        let seats = grid(indoc! {"
+        .......#.
+        ...#.....
+        .#.......
+        .........
+        ..#L....#
+        ....#....
+        .........
+        #........
+        ...#.....
+        "});
+        let seen = seats
+            .rays8(Pos::new(3, 4))
+            .filter_map(|mut ray| ray.find(|(_, c)| *c != '.'))
+            .filter(|(_, c)| *c == '#')
+            .count();
+        assert_eq!(seen, 8);
+        assert_eq!(
+            seats.ray_by(Pos::new(3, 4), (-1, 1)).last(),
+            Some((Pos::new(0, 7), '#'))
+        );
+        assert_eq!(seats.ray_by(Pos::new(3, 4), (0, 0)).count(), 0);
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum Track {
+        Empty,
+        Straight,
+        Curve,
+    }
+
+    impl TryFrom<char> for Track {
+        type Error = String;
+
+        fn try_from(c: char) -> Result<Self, Self::Error> {
+            match c {
+                ' ' => Ok(Self::Empty),
+                '-' | '|' => Ok(Self::Straight),
+                '/' | '\\' => Ok(Self::Curve),
+                _ => Err(format!("unknown track '{c}'")),
+            }
+        }
+    }
+
+    #[test]
+    fn test_try_parse() {
+        let tracks = VecGrid::<Track>::try_parse("/-\\\n| |\n\\-/").unwrap();
+        assert_eq!(tracks[Pos::new(1, 1)], Track::Empty);
+        assert_eq!(tracks[Pos::new(2, 2)], Track::Curve);
+        assert_eq!(
+            VecGrid::<Track>::try_parse("/-\\\n|x|").err(),
+            Some(ParseGridError::InvalidCell {
+                row: 1,
+                column: 1,
+                c: 'x',
+                error: "unknown track 'x'".to_owned()
+            })
+        );
+    }
+
+    #[test]
+    fn test_try_parse_with_markers() {
+        // Carts from 2018 day 13 are extracted and replaced with the track beneath them
+        let (tracks, carts) =
+            VecGrid::<Track>::try_parse_with_markers("/->\\\nv  |\n\\-</", |c| match c {
+                '>' => Some((Direction::Right, Track::Straight)),
+                '<' => Some((Direction::Left, Track::Straight)),
+                'v' => Some((Direction::Down, Track::Straight)),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(
+            carts,
+            [
+                (Pos::new(2, 0), Direction::Right),
+                (Pos::new(0, 1), Direction::Down),
+                (Pos::new(2, 2), Direction::Left)
+            ]
+        );
+        assert!(carts.iter().all(|(pos, _)| tracks[*pos] == Track::Straight));
+    }
+
+    #[test]
+    fn test_no_wrap() {
+        let grid: VecGrid<char> = SAMPLE.parse().unwrap();
+        assert_eq!(grid.get(Pos::new(3, 0)), None);
+        assert_eq!(grid.next_pos(Pos::new(0, 0), Direction::Left), None);
+        assert_eq!(grid.neighbours(Pos::new(0, 0)).flatten().count(), 2);
+    }
+
+    #[test]
+    fn test_wrap_horizontal() {
+        let grid = SAMPLE
+            .parse::<VecGrid<char>>()
+            .unwrap()
+            .with_wrap(Wrap::Horizontal);
+        assert_eq!(grid.get(Pos::new(3, 0)), Some('a'));
+        assert_eq!(grid.get(Pos::from((-1, 1))), Some('f'));
+        assert_eq!(grid.get(Pos::from((0, -1))), None);
+        assert_eq!(
+            grid.next_pos(Pos::new(0, 1), Direction::Left),
+            Some(Pos::new(2, 1))
+        );
+        assert_eq!(grid.next_pos(Pos::new(0, 1), Direction::Down), None);
+    }
+
+    #[test]
+    fn test_wrap_both() {
+        let mut grid = SAMPLE
+            .parse::<VecGrid<char>>()
+            .unwrap()
+            .with_wrap(Wrap::Both);
+        assert_eq!(grid.wrap(), Wrap::Both);
+        assert_eq!(grid[Pos::from((-1, -1))], 'f');
+        grid[Pos::new(4, 2)] = 'x';
+        assert_eq!(grid[Pos::new(1, 0)], 'x');
+        let neighbours = grid.neighbours_ex(Pos::new(0, 0)).collect::<Vec<_>>();
+        assert!(neighbours.contains(&(Direction::Left, Pos::new(2, 0), Some('c'))));
+        assert!(neighbours.contains(&(Direction::Up, Pos::new(0, 1), Some('d'))));
+        assert_eq!(grid.neighbours8(Pos::new(1, 1)).flatten().count(), 8);
+    }
+
+    #[test]
+    fn test_wrap_toboggan_trajectory() {
+        // 2020 day 3 example, where the map repeats to the right
+        let grid = "..##.......\n#...#...#..\n.#....#..#.\n..#.#...#.#\n.#...##..#.\n..#.##.....\n.#.#.#....#\n.#........#\n#.##...#...\n#...##....#\n.#..#...#.#"
+            .parse::<VecGrid<char>>()
+            .unwrap()
+            .with_wrap(Wrap::Horizontal);
+        let squares = (0..)
+            .map_while(|i| grid.wrap_pos(Pos::new(3 * i, i)))
+            .map(|pos| grid[pos])
+            .collect::<String>();
+        assert_eq!(squares, "..#.##.####");
+        assert_eq!(squares.chars().filter(|&c| c == '#').count(), 7);
+        // Indexing and get wrap in the same way
+        assert_eq!(grid[Pos::new(30, 10)], '#');
+        assert_eq!(grid.get(Pos::new(30, 10)), Some('#'));
+        assert_eq!(grid.get(Pos::new(30, 11)), None);
+    }
+
+    #[test]
+    fn test_wrap_empty() {
+        let empty = VecGrid::<char>::new().with_wrap(Wrap::Both);
+        assert_eq!(empty.get(Pos::new(1, 1)), None);
+        assert_eq!(empty.wrap_pos(Pos::from((-1, 0))), None);
+        let no_columns = VecGrid::<char>::from(vec![vec![], vec![]]).with_wrap(Wrap::Horizontal);
+        assert_eq!(no_columns.next_pos(Pos::new(0, 1), Direction::Left), None);
+    }
+}
@@ -0,0 +1,68 @@
+use crate::grid::{Pos, VecGrid};
+use std::ops::{Index, Range};
+
+/// Borrowed view of a rectangular region of a `VecGrid`
+///
+/// Positions are relative to the top left corner of the view
+#[derive(Clone)]
+pub struct VecGridView<'a, V> {
+    grid: &'a VecGrid<V>,
+    x: Range<usize>,
+    y: Range<usize>,
+}
+
+impl<'a, V: Clone + Copy> VecGridView<'a, V> {
+    /// Creates a new view of the supplied columns and rows of `grid`
+    pub(crate) const fn new(grid: &'a VecGrid<V>, x: Range<usize>, y: Range<usize>) -> Self {
+        Self { grid, x, y }
+    }
+
+    /// Gets the width of the view
+    pub fn width(&self) -> usize {
+        self.x.len()
+    }
+
+    /// Gets the height of the view
+    pub fn height(&self) -> usize {
+        self.y.len()
+    }
+
+    /// Checks whether the supplied position exists within the view
+    pub fn contains(&self, pos: Pos) -> bool {
+        pos.y >= 0 && pos.y < self.height() as isize && pos.x >= 0 && pos.x < self.width() as isize
+    }
+
+    /// Gets the element at the supplied position or None if it is outside of the view
+    pub fn get(&self, pos: Pos) -> Option<V> {
+        self.contains(pos).then(|| self[pos])
+    }
+
+    /// Returns an iterator over (position, value) tuples of the view in row order
+    pub fn iter(&self) -> impl Iterator<Item = (Pos, &'a V)> + '_ {
+        let grid = self.grid;
+        let origin = Pos::new(self.x.start, self.y.start);
+        (0..self.height()).flat_map(move |y| {
+            (0..self.width()).map(move |x| {
+                let pos = Pos::new(x, y);
+                (pos, &grid[origin + (x, y)])
+            })
+        })
+    }
+
+    /// Copies the view into a new `VecGrid`
+    pub fn to_grid(&self) -> VecGrid<V> {
+        let data = (0..self.height())
+            .map(|y| (0..self.width()).map(|x| self[Pos::new(x, y)]).collect())
+            .collect::<Vec<Vec<_>>>();
+        data.into()
+    }
+}
+
+impl<V: Clone + Copy> Index<Pos> for VecGridView<'_, V> {
+    type Output = V;
+
+    fn index(&self, index: Pos) -> &Self::Output {
+        assert!(self.contains(index), "{:?} is outside of the view", index);
+        &self.grid[index + (self.x.start, self.y.start)]
+    }
+}
@@ -0,0 +1,516 @@
+//! A module for working with sets of integer intervals
+//!
+//! `RangeSet` stores a set of values as sorted, non-overlapping inclusive ranges so that
+//! large ranges (e.g. blocked IP addresses) can be combined without visiting every value.
+//! `CuboidSet` does the same for volumes of cells in `D` dimensions (e.g. turning on and
+//! off cuboids of a reactor).
+
+use crate::grid::PosN;
+use std::collections::HashMap;
+use std::ops::{Add, RangeInclusive, Sub};
+
+/// Integer types that can be used as the bounds of a range in a `RangeSet`
+pub trait Discrete: Copy + Ord + Add<Output = Self> + Sub<Output = Self> {
+    /// The value 0
+    const ZERO: Self;
+    /// The value 1 (i.e. the distance between neighbouring values)
+    const ONE: Self;
+    /// The smallest possible value
+    const MIN: Self;
+    /// The largest possible value
+    const MAX: Self;
+
+    /// Adds `rhs`, returning None if the result would overflow
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+
+    /// Subtracts `rhs`, returning None if the result would overflow
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+}
+
+macro_rules! impl_discrete {
+    ($($t:ty),*) => {
+        $(
+            impl Discrete for $t {
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+                const MIN: Self = <$t>::MIN;
+                const MAX: Self = <$t>::MAX;
+
+                fn checked_add(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_add(self, rhs)
+                }
+
+                fn checked_sub(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_sub(self, rhs)
+                }
+            }
+        )*
+    };
+}
+
+impl_discrete!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+/// Checks whether `a` fully contains `b`
+pub fn range_contains<T: Ord>(a: &RangeInclusive<T>, b: &RangeInclusive<T>) -> bool {
+    a.start() <= b.start() && b.end() <= a.end()
+}
+
+/// Checks whether `a` and `b` have any values in common
+pub fn range_overlaps<T: Ord>(a: &RangeInclusive<T>, b: &RangeInclusive<T>) -> bool {
+    a.start() <= b.end() && b.start() <= a.end()
+}
+
+/// Gets the range of values that are in both `a` and `b` (if there are any)
+pub fn range_intersection<T: Copy + Ord>(
+    a: &RangeInclusive<T>,
+    b: &RangeInclusive<T>,
+) -> Option<RangeInclusive<T>> {
+    let (start, end) = (*a.start().max(b.start()), *a.end().min(b.end()));
+    (start <= end).then_some(start..=end)
+}
+
+/// Set of values stored as sorted ranges that neither overlap nor touch
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RangeSet<T> {
+    ranges: Vec<RangeInclusive<T>>,
+}
+
+impl<T> Default for RangeSet<T> {
+    fn default() -> Self {
+        Self { ranges: Vec::new() }
+    }
+}
+
+impl<T: Discrete> From<RangeInclusive<T>> for RangeSet<T> {
+    fn from(range: RangeInclusive<T>) -> Self {
+        std::iter::once(range).collect()
+    }
+}
+
+impl<T: Discrete> FromIterator<RangeInclusive<T>> for RangeSet<T> {
+    fn from_iter<I: IntoIterator<Item = RangeInclusive<T>>>(iter: I) -> Self {
+        let mut set = Self::new();
+        for range in iter {
+            set.insert(range);
+        }
+        set
+    }
+}
+
+impl<T: Discrete> RangeSet<T> {
+    /// Creates a new empty `RangeSet`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Iterates over the ranges in the set from lowest to highest
+    pub fn iter(&self) -> impl Iterator<Item = &RangeInclusive<T>> + '_ {
+        self.ranges.iter()
+    }
+
+    /// Checks whether the set contains no values
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Gets the total number of values covered by the set
+    ///
+    /// Returns None if there are too many to count in a `T` (e.g. every `u32` is in the set)
+    pub fn coverage(&self) -> Option<T> {
+        self.ranges.iter().try_fold(T::ZERO, |total, range| {
+            range
+                .end()
+                .checked_sub(*range.start())?
+                .checked_add(T::ONE)?
+                .checked_add(total)
+        })
+    }
+
+    /// Gets the lowest value in the set
+    pub fn min(&self) -> Option<T> {
+        self.ranges.first().map(|range| *range.start())
+    }
+
+    /// Gets the highest value in the set
+    pub fn max(&self) -> Option<T> {
+        self.ranges.last().map(|range| *range.end())
+    }
+
+    /// Checks whether the set contains `value`
+    pub fn contains(&self, value: T) -> bool {
+        self.contains_range(&(value..=value))
+    }
+
+    /// Checks whether the set contains every value in `range`
+    pub fn contains_range(&self, range: &RangeInclusive<T>) -> bool {
+        // Only the last range that starts at or before the range can contain it
+        let index = self.ranges.partition_point(|r| r.start() <= range.start());
+        index > 0 && range_contains(&self.ranges[index - 1], range)
+    }
+
+    /// Checks whether the set contains any of the values in `range`
+    pub fn overlaps(&self, range: &RangeInclusive<T>) -> bool {
+        let index = self.ranges.partition_point(|r| r.end() < range.start());
+        self.ranges
+            .get(index)
+            .is_some_and(|r| range_overlaps(r, range))
+    }
+
+    /// Adds all values in `range` to the set, merging it with any ranges that it overlaps or touches
+    pub fn insert(&mut self, range: RangeInclusive<T>) {
+        if range.is_empty() {
+            return;
+        }
+        let (mut start, mut end) = range.into_inner();
+        // Find the ranges that will be merged (those that end just before the start or later
+        // and start just after the end or earlier)
+        let first = self
+            .ranges
+            .partition_point(|r| *r.end() < start && *r.end() + T::ONE < start);
+        let last = self
+            .ranges
+            .partition_point(|r| *r.start() <= end || *r.start() - T::ONE <= end);
+        if first < last {
+            start = start.min(*self.ranges[first].start());
+            end = end.max(*self.ranges[last - 1].end());
+        }
+        self.ranges
+            .splice(first..last, std::iter::once(start..=end));
+    }
+
+    /// Removes all values in `range` from the set, splitting any ranges that it is within
+    pub fn remove(&mut self, range: &RangeInclusive<T>) {
+        if range.is_empty() {
+            return;
+        }
+        let first = self.ranges.partition_point(|r| r.end() < range.start());
+        let last = self.ranges.partition_point(|r| r.start() <= range.end());
+        if first >= last {
+            return;
+        }
+        // Keep the parts of the first and last ranges that stick out either side
+        let mut kept = Vec::with_capacity(2);
+        if self.ranges[first].start() < range.start() {
+            kept.push(*self.ranges[first].start()..=*range.start() - T::ONE);
+        }
+        if self.ranges[last - 1].end() > range.end() {
+            kept.push(*range.end() + T::ONE..=*self.ranges[last - 1].end());
+        }
+        self.ranges.splice(first..last, kept);
+    }
+
+    /// Creates a new set containing the values that are in either set
+    #[must_use]
+    pub fn union(&self, other: &Self) -> Self {
+        let mut union = self.clone();
+        for range in &other.ranges {
+            union.insert(range.clone());
+        }
+        union
+    }
+
+    /// Creates a new set containing the values that are in both sets
+    #[must_use]
+    pub fn intersection(&self, other: &Self) -> Self {
+        // Walk through both sets together, always advancing the range that ends first
+        let mut ranges = Vec::new();
+        let (mut a, mut b) = (
+            self.ranges.iter().peekable(),
+            other.ranges.iter().peekable(),
+        );
+        while let (Some(&x), Some(&y)) = (a.peek(), b.peek()) {
+            if let Some(overlap) = range_intersection(x, y) {
+                ranges.push(overlap);
+            }
+            if x.end() < y.end() {
+                a.next();
+            } else {
+                b.next();
+            }
+        }
+        Self { ranges }
+    }
+
+    /// Creates a new set containing the values that are in this set but not in `other`
+    #[must_use]
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut difference = self.clone();
+        for range in &other.ranges {
+            difference.remove(range);
+        }
+        difference
+    }
+
+    /// Iterates over the ranges of values within `bounds` that are not in the set
+    pub fn gaps(&self, bounds: RangeInclusive<T>) -> impl Iterator<Item = RangeInclusive<T>> + '_ {
+        let (start, end) = bounds.into_inner();
+        let mut next = Some(start);
+        self.ranges
+            .iter()
+            .map(Some)
+            .chain(std::iter::once(None))
+            .filter_map(move |range| {
+                let from = next.filter(|&from| from <= end)?;
+                match range {
+                    Some(range) => {
+                        // The next gap can only start after this range (unless it is the end of all values)
+                        next = (*range.end() < T::MAX).then(|| from.max(*range.end() + T::ONE));
+                        (from < *range.start()).then(|| from..=(*range.start() - T::ONE).min(end))
+                    }
+                    None => {
+                        next = None;
+                        Some(from..=end)
+                    }
+                }
+            })
+    }
+}
+
+/// Cuboid of cells in `D` dimensions between two corners (inclusive)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cuboid<const D: usize> {
+    pub min: PosN<D>,
+    pub max: PosN<D>,
+}
+
+impl<const D: usize> Cuboid<D> {
+    /// Creates a new `Cuboid` between the two supplied corners
+    pub fn new(a: PosN<D>, b: PosN<D>) -> Self {
+        Self {
+            min: a.min(b),
+            max: a.max(b),
+        }
+    }
+
+    /// Creates a new `Cuboid` from the range covered in each dimension
+    ///
+    /// # Panics
+    ///
+    /// If any of the ranges are empty
+    pub fn from_ranges(ranges: [RangeInclusive<isize>; D]) -> Self {
+        assert!(ranges.iter().all(|range| !range.is_empty()));
+        Self {
+            min: PosN(ranges.clone().map(|range| *range.start())),
+            max: PosN(ranges.map(|range| *range.end())),
+        }
+    }
+
+    /// Gets the range covered in the specified dimension
+    pub fn range(&self, dimension: usize) -> RangeInclusive<isize> {
+        self.min[dimension]..=self.max[dimension]
+    }
+
+    /// Calculates the number of cells within the cuboid
+    pub fn volume(&self) -> isize {
+        (0..D).map(|d| self.max[d] - self.min[d] + 1).product()
+    }
+
+    /// Checks whether the cuboid contains `pos`
+    pub fn contains(&self, pos: &PosN<D>) -> bool {
+        (0..D).all(|d| self.range(d).contains(&pos[d]))
+    }
+
+    /// Checks whether the cuboid fully contains `other`
+    pub fn contains_cuboid(&self, other: &Self) -> bool {
+        (0..D).all(|d| range_contains(&self.range(d), &other.range(d)))
+    }
+
+    /// Calculates the intersection of this `Cuboid` with another
+    ///
+    /// Returns a new `Cuboid` if they intersect or None if they don't
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let (min, max) = (self.min.max(other.min), self.max.min(other.max));
+        (0..D)
+            .all(|d| min[d] <= max[d])
+            .then_some(Self { min, max })
+    }
+}
+
+/// Set of cells made up by turning on and off `Cuboid`s
+///
+/// Rather than splitting cuboids into smaller ones when they overlap, each overlap is
+/// stored as a cuboid with a count that cancels out the cells that have been counted
+/// twice (or turned off), so the volume is the sum of each cuboid's volume * count
+#[derive(Debug, Clone, Default)]
+pub struct CuboidSet<const D: usize> {
+    cuboids: HashMap<Cuboid<D>, isize>,
+}
+
+impl<const D: usize> CuboidSet<D> {
+    /// Creates a new empty `CuboidSet`
+    pub fn new() -> Self {
+        Self {
+            cuboids: HashMap::new(),
+        }
+    }
+
+    /// Turns on all cells within `cuboid`
+    pub fn insert(&mut self, cuboid: Cuboid<D>) {
+        self.apply(cuboid, true);
+    }
+
+    /// Turns off all cells within `cuboid`
+    pub fn remove(&mut self, cuboid: Cuboid<D>) {
+        self.apply(cuboid, false);
+    }
+
+    /// Calculates the number of cells that are on
+    pub fn volume(&self) -> isize {
+        self.cuboids
+            .iter()
+            .map(|(cuboid, &count)| cuboid.volume() * count)
+            .sum()
+    }
+
+    /// Checks whether the cell at `pos` is on
+    pub fn contains(&self, pos: &PosN<D>) -> bool {
+        self.cuboids
+            .iter()
+            .filter(|(cuboid, _)| cuboid.contains(pos))
+            .map(|(_, &count)| count)
+            .sum::<isize>()
+            > 0
+    }
+
+    /// Turns the cells within `cuboid` on or off
+    fn apply(&mut self, cuboid: Cuboid<D>, on: bool) {
+        let mut updates: HashMap<Cuboid<D>, isize> = HashMap::new();
+        // Existing cuboids that are entirely covered by the new one no longer need to be tracked
+        // as their cells will be replaced, otherwise cancel out the cells within the overlap
+        self.cuboids.retain(|existing, &mut count| {
+            match existing.intersection(&cuboid) {
+                Some(overlap) if overlap == *existing => return false,
+                Some(overlap) => *updates.entry(overlap).or_insert(0) -= count,
+                None => {}
+            }
+            true
+        });
+        if on {
+            *updates.entry(cuboid).or_insert(0) += 1;
+        }
+        for (cuboid, count) in updates {
+            let total = self.cuboids.entry(cuboid).or_insert(0);
+            *total += count;
+            if *total == 0 {
+                // Turned on and then off again, stop tracking it
+                self.cuboids.remove(&cuboid);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_merges() {
+        let mut set: RangeSet<usize> = [5..=8, 0..=2, 4..=7].into_iter().collect();
+        assert_eq!(set.iter().cloned().collect::<Vec<_>>(), vec![0..=2, 4..=8]);
+        assert_eq!(set.coverage(), Some(8));
+        // Touching ranges are merged
+        set.insert(3..=3);
+        assert_eq!(set.iter().cloned().collect::<Vec<_>>(), vec![0..=8]);
+        set.insert(20..=30);
+        set.insert(10..=12);
+        assert_eq!(
+            set.iter().cloned().collect::<Vec<_>>(),
+            vec![0..=8, 10..=12, 20..=30]
+        );
+        assert!(set.contains(11) && !set.contains(9));
+        assert!(set.contains_range(&(21..=30)) && !set.contains_range(&(8..=10)));
+        assert!(set.overlaps(&(8..=10)) && !set.overlaps(&(13..=19)));
+        assert_eq!((set.min(), set.max()), (Some(0), Some(30)));
+    }
+
+    #[test]
+    fn test_coverage_limits() {
+        assert_eq!(RangeSet::<u32>::new().coverage(), Some(0));
+        assert_eq!(RangeSet::from(1..=u32::MAX).coverage(), Some(u32::MAX));
+        // One more value than a u32 can count
+        assert_eq!(RangeSet::from(0..=u32::MAX).coverage(), None);
+        let halves: RangeSet<u8> = [0..=100, 102..=u8::MAX].into_iter().collect();
+        assert_eq!(halves.coverage(), Some(255));
+        // The distance between the ends of a signed range can overflow too
+        assert_eq!(RangeSet::from(-50i8..=50).coverage(), Some(101));
+        assert_eq!(RangeSet::from(-100i8..=100).coverage(), None);
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut set = RangeSet::from(-10..=10);
+        set.remove(&(-2..=3));
+        set.remove(&(8..=20));
+        assert_eq!(
+            set.iter().cloned().collect::<Vec<_>>(),
+            vec![-10..=-3, 4..=7]
+        );
+        set.remove(&(-10..=7));
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a: RangeSet<i32> = [0..=10, 20..=30].into_iter().collect();
+        let b: RangeSet<i32> = [5..=25, 28..=40].into_iter().collect();
+        assert_eq!(
+            a.union(&b).iter().cloned().collect::<Vec<_>>(),
+            vec![0..=40]
+        );
+        assert_eq!(
+            a.intersection(&b).iter().cloned().collect::<Vec<_>>(),
+            vec![5..=10, 20..=25, 28..=30]
+        );
+        assert_eq!(
+            a.difference(&b).iter().cloned().collect::<Vec<_>>(),
+            vec![0..=4, 26..=27]
+        );
+    }
+
+    #[test]
+    fn test_gaps() {
+        // 2016 day 20 example
+        let blocked: RangeSet<u32> = [5..=8, 0..=2, 4..=7].into_iter().collect();
+        assert_eq!(blocked.gaps(0..=9).collect::<Vec<_>>(), vec![3..=3, 9..=9]);
+        assert_eq!(blocked.gaps(3..=5).collect::<Vec<_>>(), vec![3..=3]);
+        assert_eq!(blocked.gaps(1..=2).count(), 0);
+        // Ranges that reach the end of all values
+        let blocked = RangeSet::from(250u8..=u8::MAX);
+        assert_eq!(blocked.gaps(0..=u8::MAX).collect::<Vec<_>>(), vec![0..=249]);
+        assert_eq!(
+            RangeSet::new().gaps(0..=u8::MAX).collect::<Vec<_>>(),
+            vec![0..=255]
+        );
+    }
+
+    #[test]
+    fn test_cuboids() {
+        let cuboid = Cuboid::from_ranges([10..=12, 10..=12, 10..=12]);
+        assert_eq!(cuboid.volume(), 27);
+        assert!(cuboid.contains(&PosN([11, 12, 10])));
+        assert!(cuboid.contains_cuboid(&Cuboid::new(PosN([11, 11, 11]), PosN([10, 10, 10]))));
+        assert_eq!(
+            cuboid.intersection(&Cuboid::from_ranges([11..=13, 11..=13, 11..=13])),
+            Some(Cuboid::from_ranges([11..=12, 11..=12, 11..=12]))
+        );
+        assert_eq!(
+            cuboid.intersection(&Cuboid::from_ranges([13..=13, 10..=12, 10..=12])),
+            None
+        );
+    }
+
+    #[test]
+    fn test_cuboid_set_2021_day22_example() {
+        let mut set = CuboidSet::new();
+        set.insert(Cuboid::from_ranges([10..=12, 10..=12, 10..=12]));
+        assert_eq!(set.volume(), 27);
+        set.insert(Cuboid::from_ranges([11..=13, 11..=13, 11..=13]));
+        assert_eq!(set.volume(), 27 + 19);
+        set.remove(Cuboid::from_ranges([9..=11, 9..=11, 9..=11]));
+        assert_eq!(set.volume(), 46 - 8);
+        set.insert(Cuboid::from_ranges([10..=10, 10..=10, 10..=10]));
+        assert_eq!(set.volume(), 39);
+        assert!(set.contains(&PosN([10, 10, 10])));
+        assert!(!set.contains(&PosN([11, 11, 10])));
+    }
+}
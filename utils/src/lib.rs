@@ -1,2 +1,9 @@
+pub mod automaton;
+pub mod circuit;
+pub mod cycle;
+pub mod expr;
 pub mod grid;
+pub mod interval;
+pub mod math;
 pub mod ocr;
+pub mod search;
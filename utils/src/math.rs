@@ -0,0 +1,319 @@
+//! A module of number theory functions (modular arithmetic, the Chinese remainder theorem and factorisation)
+//!
+//! Modular functions take `i64` values and use `i128` for intermediate results so
+//! that moduli up to `i64::MAX` can be used without overflowing.
+
+use std::collections::HashMap;
+
+/// Calculates the greatest common divisor of `a` and `b` (which is always positive)
+pub fn gcd(a: i64, b: i64) -> i64 {
+    extended_gcd(a, b).0
+}
+
+/// Calculates the lowest common multiple of `a` and `b`
+pub fn lcm(a: i64, b: i64) -> i64 {
+    if a == 0 || b == 0 {
+        return 0;
+    }
+    (a / gcd(a, b) * b).abs()
+}
+
+/// Uses the extended Euclidean algorithm to find (g, x, y) where g is the greatest
+/// common divisor of `a` and `b` and `ax + by = g`
+pub fn extended_gcd(a: i64, b: i64) -> (i64, i64, i64) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1, 0);
+    let (mut old_y, mut y) = (0, 1);
+    while r != 0 {
+        let quotient = old_r / r;
+        (old_r, r) = (r, old_r - quotient * r);
+        (old_x, x) = (x, old_x - quotient * x);
+        (old_y, y) = (y, old_y - quotient * y);
+    }
+    if old_r < 0 {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+/// Calculates `a * b mod m` (in the range `0..m`) without overflowing
+pub fn mod_mul(a: i64, b: i64, m: i64) -> i64 {
+    (i128::from(a) * i128::from(b)).rem_euclid(i128::from(m)) as i64
+}
+
+/// Calculates `base ^ exp mod m` (in the range `0..m`) using exponentiation by squaring
+pub fn mod_pow(base: i64, exp: u64, m: i64) -> i64 {
+    let mut result = 1 % m;
+    let mut base = base.rem_euclid(m);
+    let mut exp = exp;
+    while exp > 0 {
+        if exp % 2 == 1 {
+            result = mod_mul(result, base, m);
+        }
+        base = mod_mul(base, base, m);
+        exp /= 2;
+    }
+    result
+}
+
+/// Calculates x such that `a * x = 1 mod m` (in the range `0..m`)
+///
+/// Returns None if there is no inverse (`a` and `m` are not coprime)
+pub fn mod_inverse(a: i64, m: i64) -> Option<i64> {
+    let (g, x, _) = extended_gcd(a.rem_euclid(m), m);
+    (g == 1).then(|| x.rem_euclid(m))
+}
+
+/// Affine transformation `f(x) = ax + b mod m` (e.g. a shuffle of a deck of m cards)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Affine {
+    pub a: i64,
+    pub b: i64,
+    pub m: i64,
+}
+
+impl Affine {
+    /// Creates a new `Affine` transformation `f(x) = ax + b mod m`
+    pub const fn new(a: i64, b: i64, m: i64) -> Self {
+        Self {
+            a: a.rem_euclid(m),
+            b: b.rem_euclid(m),
+            m,
+        }
+    }
+
+    /// Creates the transformation that leaves all values unchanged `f(x) = x mod m`
+    pub const fn identity(m: i64) -> Self {
+        Self::new(1, 0, m)
+    }
+
+    /// Applies the transformation to `x`
+    pub fn apply(&self, x: i64) -> i64 {
+        (i128::from(self.a) * i128::from(x) + i128::from(self.b)).rem_euclid(i128::from(self.m))
+            as i64
+    }
+
+    /// Creates a transformation that applies this one followed by `other`
+    ///
+    /// Turning f(x) = ax + b and g(x) = cx + d into g(f(x)) = acx + bc + d
+    #[must_use]
+    pub fn then(&self, other: &Self) -> Self {
+        Self::new(
+            mod_mul(self.a, other.a, self.m),
+            other.apply(self.b),
+            self.m,
+        )
+    }
+
+    /// Creates a transformation that applies this one `n` times using exponentiation by squaring
+    #[must_use]
+    pub fn pow(&self, n: u64) -> Self {
+        let mut result = Self::identity(self.m);
+        let mut f = *self;
+        let mut n = n;
+        while n > 0 {
+            if n % 2 == 1 {
+                result = result.then(&f);
+            }
+            f = f.then(&f);
+            n /= 2;
+        }
+        result
+    }
+
+    /// Creates the transformation that undoes this one (i.e. maps f(x) back to x)
+    ///
+    /// Returns None if `a` is not coprime with `m` (and so multiple values map to the same result)
+    pub fn inverse(&self) -> Option<Self> {
+        // x = (y - b) / a
+        let inverse = mod_inverse(self.a, self.m)?;
+        Some(Self::new(
+            inverse,
+            mod_mul(-self.b, inverse, self.m),
+            self.m,
+        ))
+    }
+}
+
+/// Uses the Chinese remainder theorem to find the smallest non-negative x that satisfies
+/// all of the supplied `(remainder, modulus)` congruences (i.e. `x = remainder mod modulus`)
+///
+/// The moduli don't need to be coprime. Returns (x, the lowest common multiple of all moduli)
+/// or None if there is no solution (or the lowest common multiple doesn't fit in an `i64`)
+pub fn crt(congruences: &[(i64, i64)]) -> Option<(i64, i64)> {
+    congruences
+        .iter()
+        .try_fold((0, 1), |(x, m1), &(remainder, m2)| {
+            // Combine x = x mod m1 with x = remainder mod m2
+            let (g, p, _) = extended_gcd(m1, m2);
+            let difference = remainder.checked_sub(x)?;
+            if difference % g != 0 {
+                return None;
+            }
+            let modulus = (m1 / g).checked_mul(m2)?;
+            let k = (i128::from(difference / g) * i128::from(p)).rem_euclid(i128::from(m2 / g));
+            let x = (i128::from(x) + i128::from(m1) * k).rem_euclid(i128::from(modulus));
+            Some((x as i64, modulus))
+        })
+}
+
+/// Uses the baby-step giant-step algorithm to find the smallest x such that `base ^ x = target mod m`
+///
+/// `base` must be coprime with `m` (e.g. `m` is prime). Returns None if there is no such x
+pub fn discrete_log(base: i64, target: i64, m: i64) -> Option<u64> {
+    let n = (m as f64).sqrt().ceil() as u64;
+    // Baby steps: store base ^ j for all j < n
+    let mut table = HashMap::new();
+    let mut value = 1 % m;
+    for j in 0..n {
+        table.entry(value).or_insert(j);
+        value = mod_mul(value, base, m);
+    }
+    // Giant steps: check target * base ^ (-i * n) for all i < n
+    let factor = mod_pow(mod_inverse(base, m)?, n, m);
+    let mut gamma = target.rem_euclid(m);
+    for i in 0..n {
+        if let Some(j) = table.get(&gamma) {
+            return Some(i * n + j);
+        }
+        gamma = mod_mul(gamma, factor, m);
+    }
+    None
+}
+
+/// Gets all of the divisors of `n` (including 1 and `n`) from lowest to highest
+pub fn divisors(n: usize) -> Vec<usize> {
+    let (mut low, mut high) = (Vec::new(), Vec::new());
+    for d in (1..).take_while(|d| d * d <= n) {
+        if n.is_multiple_of(d) {
+            low.push(d);
+            if d * d != n {
+                high.push(n / d);
+            }
+        }
+    }
+    low.extend(high.into_iter().rev());
+    low
+}
+
+/// Gets the prime factors of `n` as (prime, power) from lowest to highest
+pub fn prime_factors(n: usize) -> Vec<(usize, u32)> {
+    let mut factors = Vec::new();
+    let mut n = n;
+    let mut p = 2;
+    while p * p <= n {
+        let mut power = 0;
+        while n.is_multiple_of(p) {
+            n /= p;
+            power += 1;
+        }
+        if power > 0 {
+            factors.push((p, power));
+        }
+        p += 1;
+    }
+    if n > 1 {
+        factors.push((n, 1));
+    }
+    factors
+}
+
+/// Uses the sieve of Eratosthenes to find all primes up to and including `limit`
+pub fn primes(limit: usize) -> Vec<usize> {
+    let mut prime = vec![true; limit + 1];
+    for p in (2..).take_while(|p| p * p <= limit) {
+        if prime[p] {
+            for multiple in (p * p..=limit).step_by(p) {
+                prime[multiple] = false;
+            }
+        }
+    }
+    (2..=limit).filter(|&p| prime[p]).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gcd() {
+        assert_eq!(gcd(12, 18), 6);
+        assert_eq!(gcd(-12, 18), 6);
+        assert_eq!(lcm(4, 6), 12);
+        let (g, x, y) = extended_gcd(240, 46);
+        assert_eq!(g, 2);
+        assert_eq!(240 * x + 46 * y, 2);
+    }
+
+    #[test]
+    fn test_modular() {
+        assert_eq!(mod_pow(7, 8, 20_201_227), 5_764_801);
+        assert_eq!(mod_pow(2, 0, 1), 0);
+        assert_eq!(mod_pow(-2, 3, 5), 2);
+        assert_eq!(mod_inverse(3, 11), Some(4));
+        assert_eq!(mod_inverse(-3, 11), Some(7));
+        assert_eq!(mod_inverse(4, 8), None);
+        // Values that would overflow an i64 when multiplied
+        let m = 119_315_717_514_047;
+        let inverse = mod_inverse(101_741_582_076_661, m).unwrap();
+        assert_eq!(mod_mul(inverse, 101_741_582_076_661, m), 1);
+    }
+
+    #[test]
+    fn test_affine() {
+        let f = Affine::new(3, -2, 11);
+        let g = Affine::new(5, 4, 11);
+        assert_eq!(f.apply(4), 10);
+        assert_eq!(f.then(&g).apply(4), g.apply(f.apply(4)));
+        assert_eq!(f.pow(5).apply(7), (0..5).fold(7, |x, _| f.apply(x)));
+        assert_eq!(f.pow(0), Affine::identity(11));
+        let inverse = f.inverse().unwrap();
+        assert!((0..11).all(|x| inverse.apply(f.apply(x)) == x));
+        assert_eq!(Affine::new(2, 0, 10).inverse(), None);
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        // 2020 day 13 example
+        assert_eq!(
+            crt(&[(0, 17), (-2, 13), (-3, 19)]),
+            Some((3417, 17 * 13 * 19))
+        );
+        // Moduli that are not coprime
+        assert_eq!(crt(&[(2, 4), (4, 6)]), Some((10, 12)));
+        assert_eq!(crt(&[(1, 4), (2, 6)]), None);
+        assert_eq!(crt(&[]), Some((0, 1)));
+    }
+
+    #[test]
+    fn test_crt_large_moduli() {
+        assert_eq!(
+            crt(&[(1, 1_000_000_007), (2, 998_244_353)]),
+            Some((993_328_913_953_302_350, 998_244_359_987_710_471))
+        );
+        // The lowest common multiple is too big for an i64
+        assert_eq!(crt(&[(1, i64::MAX), (2, i64::MAX - 1)]), None);
+        assert_eq!(crt(&[(1, 1_000_000_007), (2, 998_244_353), (3, 11)]), None);
+    }
+
+    #[test]
+    fn test_discrete_log() {
+        // 2020 day 25 example
+        assert_eq!(discrete_log(7, 5_764_801, 20_201_227), Some(8));
+        assert_eq!(discrete_log(7, 17_807_724, 20_201_227), Some(11));
+        assert_eq!(discrete_log(2, 1, 11), Some(0));
+        assert_eq!(discrete_log(4, 3, 7), None);
+    }
+
+    #[test]
+    fn test_factors() {
+        assert_eq!(divisors(1), vec![1]);
+        assert_eq!(divisors(36), vec![1, 2, 3, 4, 6, 9, 12, 18, 36]);
+        assert_eq!(prime_factors(360), vec![(2, 3), (3, 2), (5, 1)]);
+        assert_eq!(prime_factors(97), vec![(97, 1)]);
+        assert_eq!(primes(30), vec![2, 3, 5, 7, 11, 13, 17, 19, 23, 29]);
+        assert_eq!(primes(1), Vec::<usize>::new());
+    }
+}
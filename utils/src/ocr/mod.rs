@@ -1,47 +1,218 @@
+use crate::grid::{MapGrid, Pos, VecGrid};
+use itertools::Itertools;
 use lazy_static::lazy_static;
 use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
 use std::fmt;
 use std::iter::FromIterator;
+use std::ops::Range;
+use std::path::Path;
 use std::str::FromStr;
 use thiserror::Error;
 
-#[derive(Error, Debug, PartialEq)]
+#[derive(Error, Debug, Clone, PartialEq)]
 pub enum OcrStringError {
-    #[error("invalid font height (expected 6 or 10, found {height})")]
+    #[error("invalid font height (no font found with height {height})")]
     InvalidHeight { height: usize },
+    #[error("unknown glyph at index {index}:\n{art}")]
+    UnknownGlyph { index: usize, art: String },
+    #[error("invalid font dictionary (expected {expected} chars, found {found})")]
+    InvalidDictionary { expected: usize, found: usize },
+    #[error("duplicate glyph in font ('{c}' is drawn the same as '{existing}')")]
+    DuplicateGlyph { c: char, existing: char },
+    #[error("failed to read font file {path}: {error}")]
+    FontFile { path: String, error: String },
 }
 
-type CharHash = u32;
+/// Bitmask of the lit pixels in each row of a char
+type CharHash = Vec<u64>;
 pub type Point = (usize, usize);
 
-#[derive(Clone)]
-enum Font {
-    Mode6x4,
-    Mode10x6,
-}
-
-const ALPHABETS: [(Font, &str, &str); 2] = [
-    (Font::Mode6x4, "ABCEFGHIJKLOPRSUYZ", include_str!("6x4.txt")),
-    (Font::Mode10x6, "ABCEFGHJKLNPRXZ", include_str!("10x6.txt")),
+/// Letters that have been seen in puzzles, followed by the remaining letters and digits drawn in the same style
+const ALPHABETS: [(&str, &str); 4] = [
+    ("ABCEFGHIJKLOPRSUYZ", include_str!("6x4.txt")),
+    ("DMNQTVWX0123456789", include_str!("6x4_extended.txt")),
+    ("ABCEFGHJKLNPRXZ", include_str!("10x6.txt")),
+    ("DIMOQSTUVWY0123456789", include_str!("10x6_extended.txt")),
 ];
 
+/// Heights of the built in fonts from smallest to largest along with the number of columns
+/// from the start of one char to the next (known without loading the fonts so that they can
+/// be used while the fonts themselves are being parsed)
+const FONT_SIZES: [(usize, usize); 2] = [(6, 5), (10, 8)];
+
+/// Most lit pixels that a group of columns can have and still be treated as noise rather than a char
+const MAX_STRAY_PIXELS: usize = 2;
+
 lazy_static! {
-    static ref CHAR_LOOKUP: HashMap<CharHash, char> = {
-        // Pre-compute the hashes for all known letters
-        let mut m = HashMap::new();
-        for (_font, chars, dictionary) in ALPHABETS {
-            // Parse the dictionary which will group points
-            let ocr = dictionary.parse::<OcrString>().unwrap();
-            for (points, letter) in ocr.grouped.iter().zip(chars.chars()) {
-                let code = hash_char(points.iter());
-                assert!(m.insert(code, letter).is_none());
+    static ref FONTS: Vec<Font> = {
+        // Pre-compute the hashes for all known letters, combining the alphabets of the same height
+        let mut fonts: Vec<Font> = Vec::new();
+        for (chars, dictionary) in ALPHABETS {
+            let font = Font::new(chars, dictionary).unwrap();
+            match fonts.iter_mut().find(|f| f.height == font.height) {
+                Some(existing) => existing.merge(font).unwrap(),
+                None => fonts.push(font),
             }
         }
-        m
+        fonts
+    };
+}
+
+/// The closest known char to a glyph
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GlyphMatch {
+    /// The char that the glyph is closest to
+    pub c: char,
+    /// The number of pixels that differ between the glyph and the char (Hamming distance)
+    pub distance: u32,
+    /// How similar the glyph is to the char, from 0.0 (no pixels in common) to 1.0 (an exact match)
+    pub confidence: f64,
+}
+
+/// A set of chars of the same height that an `OcrString` can be decoded with
+#[derive(Debug, Clone)]
+pub struct Font {
+    height: usize,
+    glyphs: HashMap<CharHash, char>,
+}
+
+impl Font {
+    /// Creates a new `Font` from a dictionary where `chars` are drawn left to right using '#'
+    /// (in the same format as the built in `6x4.txt` and `10x6.txt`)
+    pub fn new(chars: &str, dictionary: &str) -> Result<Self, OcrStringError> {
+        let ocr: OcrString = dictionary.parse()?;
+        let expected = chars.chars().count();
+        if ocr.len() != expected {
+            return Err(OcrStringError::InvalidDictionary {
+                expected,
+                found: ocr.len(),
+            });
+        }
+        let mut font = Self {
+            height: ocr.height,
+            glyphs: HashMap::new(),
+        };
+        for (points, c) in ocr.grouped.iter().zip(chars.chars()) {
+            font.insert(hash_char(points.iter(), ocr.height), c)?;
+        }
+        Ok(font)
+    }
+
+    /// Loads a `Font` from a dictionary file where `chars` are drawn left to right using '#'
+    pub fn from_file(chars: &str, path: impl AsRef<Path>) -> Result<Self, OcrStringError> {
+        let dictionary =
+            std::fs::read_to_string(&path).map_err(|error| OcrStringError::FontFile {
+                path: path.as_ref().display().to_string(),
+                error: error.to_string(),
+            })?;
+        Self::new(chars, &dictionary)
+    }
+
+    /// Gets the built in font with the supplied height (if there is one)
+    pub fn builtin(height: usize) -> Option<&'static Self> {
+        FONTS.iter().find(|font| font.height == height)
+    }
+
+    /// Gets the height of the chars in the font
+    pub const fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Adds all of the chars from `other` (which must be the same height) to this font
+    pub fn merge(&mut self, other: Self) -> Result<(), OcrStringError> {
+        if other.height != self.height {
+            return Err(OcrStringError::InvalidHeight {
+                height: other.height,
+            });
+        }
+        for (code, c) in other.glyphs {
+            self.insert(code, c)?;
+        }
+        Ok(())
+    }
+
+    fn insert(&mut self, code: CharHash, c: char) -> Result<(), OcrStringError> {
+        match self.glyphs.insert(code, c) {
+            Some(existing) => Err(OcrStringError::DuplicateGlyph { c, existing }),
+            None => Ok(()),
+        }
+    }
+
+    /// Finds the char with the fewest pixels different to `code`
+    fn nearest(&self, code: &CharHash) -> Option<GlyphMatch> {
+        if let Some(&c) = self.glyphs.get(code) {
+            return Some(GlyphMatch {
+                c,
+                distance: 0,
+                confidence: 1.0,
+            });
+        }
+        self.glyphs
+            .iter()
+            .map(|(known, &c)| {
+                let (distance, union) =
+                    code.iter()
+                        .zip(known)
+                        .fold((0, 0), |(distance, union), (a, b)| {
+                            (
+                                distance + (a ^ b).count_ones(),
+                                union + (a | b).count_ones(),
+                            )
+                        });
+                GlyphMatch {
+                    c,
+                    distance,
+                    confidence: if union == 0 {
+                        1.0
+                    } else {
+                        1.0 - f64::from(distance) / f64::from(union)
+                    },
+                }
+            })
+            // Break ties on the char so that the result doesn't depend on the map order
+            .min_by_key(|m| (m.distance, m.c))
+    }
+}
+
+/// Grid values that have a standard way of drawing chars, so that grids of them can be
+/// converted into an `OcrString` using `From`
+///
+/// This is only a convenience for the common cases, `OcrString::from_vec_grid` and
+/// `OcrString::from_map_grid` accept a predicate for grids of any other values
+pub trait Pixel {
+    /// Checks whether the pixel is lit (i.e. is part of a char)
+    fn is_lit(&self) -> bool;
+}
+
+impl Pixel for bool {
+    fn is_lit(&self) -> bool {
+        *self
+    }
+}
+
+impl Pixel for char {
+    /// '#' is used to draw the letters
+    fn is_lit(&self) -> bool {
+        *self == '#'
+    }
+}
+
+macro_rules! impl_pixel_for_int {
+    ($($t:ty),*) => {
+        $(
+            impl Pixel for $t {
+                /// 1 is used to draw the letters (e.g. white pixels output by Intcode)
+                fn is_lit(&self) -> bool {
+                    *self == 1
+                }
+            }
+        )*
     };
 }
 
+impl_pixel_for_int!(u8, u32, u64, usize, i32, i64, isize);
+
 #[derive(Debug)]
 pub struct OcrString {
     grouped: Vec<HashSet<Point>>,
@@ -50,14 +221,41 @@ pub struct OcrString {
 
 impl FromIterator<(isize, isize)> for OcrString {
     fn from_iter<I: IntoIterator<Item = (isize, isize)>>(iter: I) -> Self {
-        OcrString::new_without_bounds(iter.into_iter().map(|(x, y)| (x as usize, y as usize)))
-            .unwrap()
+        // Shift the points first so that negative coordinates can be used
+        let points = iter.into_iter().collect::<Vec<_>>();
+        let min_x = points.iter().map(|&(x, _)| x).min().unwrap_or(0);
+        let min_y = points.iter().map(|&(_, y)| y).min().unwrap_or(0);
+        OcrString::new_without_bounds(
+            points
+                .into_iter()
+                .map(|(x, y)| ((x - min_x) as usize, (y - min_y) as usize)),
+        )
     }
 }
 
 impl FromIterator<Point> for OcrString {
     fn from_iter<I: IntoIterator<Item = Point>>(iter: I) -> Self {
-        OcrString::new_without_bounds(iter.into_iter()).unwrap()
+        OcrString::new_without_bounds(iter.into_iter())
+    }
+}
+
+impl FromIterator<Pos> for OcrString {
+    fn from_iter<I: IntoIterator<Item = Pos>>(iter: I) -> Self {
+        iter.into_iter().map(|pos| (pos.x, pos.y)).collect()
+    }
+}
+
+impl<V: Clone + Copy + Pixel> From<&VecGrid<V>> for OcrString {
+    /// Builds an `OcrString` from the lit pixels of a `VecGrid`
+    fn from(grid: &VecGrid<V>) -> Self {
+        OcrString::from_vec_grid(grid, Pixel::is_lit)
+    }
+}
+
+impl<V: Clone + Pixel> From<&MapGrid<V>> for OcrString {
+    /// Builds an `OcrString` from the lit pixels of a `MapGrid`
+    fn from(grid: &MapGrid<V>) -> Self {
+        OcrString::from_map_grid(grid, Pixel::is_lit)
     }
 }
 
@@ -66,14 +264,13 @@ impl TryFrom<Vec<Vec<char>>> for OcrString {
 
     /// Builds an `OcrString` from a `Vec` of row `Vec` of `char` where '#' is used to draw the letters
     fn try_from(vec: Vec<Vec<char>>) -> Result<Self, Self::Error> {
-        let height = vec.len();
         let points = vec.iter().enumerate().flat_map(|(y, row)| {
             row.iter()
                 .enumerate()
                 .filter(|(_, c)| c == &&'#')
                 .map(move |(x, _)| (x, y))
         });
-        OcrString::new(points, (0, 0), height)
+        Ok(OcrString::new_without_bounds(points))
     }
 }
 
@@ -82,79 +279,230 @@ impl FromStr for OcrString {
 
     /// Builds an `OcrString` from ASCII art string where '#' is used to draw the letters
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let height = s.lines().count();
         let points = s.lines().enumerate().flat_map(|(y, line)| {
             line.chars()
                 .enumerate()
                 .filter(|(_, c)| c == &'#')
                 .map(move |(x, _)| (x, y))
         });
-        OcrString::new(points, (0, 0), height)
+        Ok(OcrString::new_without_bounds(points))
     }
 }
 
 impl OcrString {
     /// Creates a new `OcrString` from Points with a known top left corner `origin` and font `height`
-    fn new(
-        points: impl Iterator<Item = Point>,
-        origin: Point,
-        height: usize,
-    ) -> Result<Self, OcrStringError> {
+    ///
+    /// Chars are split up by empty columns, apart from any that touch which are split using the
+    /// most common spacing between chars (e.g. a 5 wide Y in a font that is spaced every 5 columns).
+    /// If there aren't enough separate chars to tell, the spacing of the built in font with the
+    /// same height is used instead. Groups of columns with too few lit pixels to be a char (e.g. a stray pixel between two
+    /// chars) are ignored.
+    fn new(points: impl Iterator<Item = Point>, origin: Point, height: usize) -> Self {
         let (min_x, min_y) = origin;
-        let split = match height {
-            6 => 5,
-            10 => 8,
-            _ => {
-                return Err(OcrStringError::InvalidHeight { height });
-            }
-        };
-        // Group the points - shifting their coordinates as needed
-        let mut grouped = Vec::new();
+        // Shift the points to give an effective origin of (0, 0) and then group them by column
+        let mut columns: Vec<Vec<usize>> = Vec::new();
         for (x_orig, y_orig) in points {
-            // First shift to give an effective origin of (0, 0)
             let (x, y) = (x_orig - min_x, y_orig - min_y);
-            // Then group by the char they are part of
-            let group = x / split;
-            while group >= grouped.len() {
-                grouped.push(HashSet::new());
+            if x >= columns.len() {
+                columns.resize(x + 1, Vec::new());
+            }
+            columns[x].push(y);
+        }
+        // Find the runs of columns that contain points
+        let mut spans: Vec<Range<usize>> = Vec::new();
+        for (lit, mut run) in &(0..columns.len()).group_by(|&x| !columns[x].is_empty()) {
+            if lit {
+                let start = run.next().unwrap();
+                let span = start..run.last().unwrap_or(start) + 1;
+                if span.clone().map(|x| columns[x].len()).sum::<usize>() > MAX_STRAY_PIXELS {
+                    spans.push(span);
+                }
             }
-            grouped[group].insert((x % split, y));
         }
+        let pitch = spans
+            .windows(2)
+            .map(|pair| pair[1].start - pair[0].start)
+            .counts()
+            .into_iter()
+            .max_by_key(|&(distance, count)| (count, std::cmp::Reverse(distance)))
+            .map(|(distance, _)| distance)
+            .or_else(|| {
+                FONT_SIZES
+                    .iter()
+                    .find(|&&(font_height, _)| font_height == height)
+                    .map(|&(_, pitch)| pitch)
+            });
+        // Then group by the char they are part of, shifting them so that each char starts at x = 0
+        let grouped = spans
+            .into_iter()
+            .flat_map(|span| match pitch {
+                Some(pitch) if span.len() > pitch => span
+                    .clone()
+                    .step_by(pitch)
+                    .map(|start| start..(start + pitch).min(span.end))
+                    .collect(),
+                _ => vec![span],
+            })
+            .map(|span| {
+                let start = span.start;
+                span.flat_map(|x| columns[x].iter().map(move |&y| (x - start, y)))
+                    .collect()
+            })
+            .collect();
 
-        Ok(Self { grouped, height })
+        Self { grouped, height }
     }
 
     /// Creates a new `OcrString` from Points without any prior knowledge about where they are and what font is used
-    fn new_without_bounds(points: impl Iterator<Item = Point>) -> Result<Self, OcrStringError> {
-        // First work out where the chars start (in terms of the top left of the first char) and how heigh they are
-        let points = points.collect::<Vec<_>>();
-        let mut min_x = usize::MAX;
-        let mut min_y = usize::MAX;
-        let mut max_y = 0;
-        for &(x, y) in points.iter() {
-            if x < min_x {
-                min_x = x;
-            }
-            if y < min_y {
-                min_y = y;
-            } else if y > max_y {
-                max_y = y;
-            }
+    fn new_without_bounds(points: impl Iterator<Item = Point>) -> Self {
+        // First work out where the chars start (in terms of the top left of the first char) and how high they are
+        let mut points = points.collect::<Vec<_>>();
+        if points.is_empty() {
+            return Self {
+                grouped: Vec::new(),
+                height: 0,
+            };
         }
-        let height = max_y - min_y + 1;
+        let (mut min_y, max_y) = points
+            .iter()
+            .map(|&(_, y)| y)
+            .minmax()
+            .into_option()
+            .unwrap();
+        let mut height = max_y - min_y + 1;
+        // Ignore any stray pixels above or below the chars
+        if let Some((top, text_height)) = text_rows(&points, min_y, height) {
+            points.retain(|&(_, y)| (top..top + text_height).contains(&y));
+            (min_y, height) = (top, text_height);
+        }
+        let min_x = points.iter().map(|&(x, _)| x).min().unwrap();
         // Now create a new `OcrString`
         OcrString::new(points.into_iter(), (min_x, min_y), height)
     }
 
-    /// Decodes the `OcrString` into a `String`
+    /// Builds an `OcrString` from a `VecGrid` using `lit` to decide which values are part of a char
+    ///
+    /// ```
+    /// # use utils::grid::VecGrid;
+    /// # use utils::ocr::OcrString;
+    /// let grid: VecGrid<char> = ".@@.\n@..@\n@..@\n@@@@\n@..@\n@..@".parse().unwrap();
+    /// let ocr = OcrString::from_vec_grid(&grid, |&c| c == '@');
+    /// assert_eq!(ocr.try_decode(), Ok("A".to_owned()));
+    /// ```
+    pub fn from_vec_grid<V: Clone + Copy>(grid: &VecGrid<V>, lit: impl Fn(&V) -> bool) -> Self {
+        let points = grid.rows().enumerate().flat_map(|(y, row)| {
+            row.iter()
+                .enumerate()
+                .filter(|(_, value)| lit(value))
+                .map(move |(x, _)| (x, y))
+                .collect::<Vec<_>>()
+        });
+        OcrString::new_without_bounds(points)
+    }
+
+    /// Builds an `OcrString` from a `MapGrid` using `lit` to decide which values are part of a char
+    pub fn from_map_grid<V: Clone>(grid: &MapGrid<V>, lit: impl Fn(&V) -> bool) -> Self {
+        grid.iter()
+            .filter(|(_, value)| lit(value))
+            .map(|(&pos, _)| pos)
+            .collect()
+    }
+
+    /// Decodes the `OcrString` into a `String` using the built in font for its height
+    ///
+    /// Any unknown chars will be replaced with '?', returns None if there is no font with a matching height
     pub fn decode(&self) -> Option<String> {
-        // For each char group, workout what char it is
-        let mut result: Vec<char> = Vec::new();
-        for group in self.grouped.iter() {
-            let code = hash_char(group.iter());
-            result.push(*CHAR_LOOKUP.get(&code).unwrap_or(&'?'));
+        Font::builtin(self.height)?;
+        Some(
+            self.decode_glyphs()
+                .iter()
+                .map(|glyph| *glyph.as_ref().unwrap_or(&'?'))
+                .collect(),
+        )
+    }
+
+    /// Decodes the `OcrString` into a `String`, failing on the first unknown char
+    pub fn try_decode(&self) -> Result<String, OcrStringError> {
+        self.decode_nearest(0)
+    }
+
+    /// Decodes the `OcrString` into a `String` using the supplied font, failing on the first unknown char
+    pub fn try_decode_with(&self, font: &Font) -> Result<String, OcrStringError> {
+        self.decode_nearest_with(font, 0)
+    }
+
+    /// Decodes each char of the `OcrString` individually, returning the char if it is known
+    /// or an `OcrStringError::UnknownGlyph` containing the ASCII art of the glyph if not
+    pub fn decode_glyphs(&self) -> Vec<Result<char, OcrStringError>> {
+        self.match_glyphs(0)
+            .into_iter()
+            .map(|glyph| glyph.map(|m| m.c))
+            .collect()
+    }
+
+    /// Decodes the `OcrString` into a `String` using the nearest known char for each glyph,
+    /// failing on the first glyph with more than `max_distance` pixels different to any known char
+    pub fn decode_nearest(&self, max_distance: u32) -> Result<String, OcrStringError> {
+        self.match_glyphs(max_distance)
+            .into_iter()
+            .map(|glyph| glyph.map(|m| m.c))
+            .collect()
+    }
+
+    /// Decodes the `OcrString` into a `String` using the nearest char from the supplied font for each glyph
+    pub fn decode_nearest_with(
+        &self,
+        font: &Font,
+        max_distance: u32,
+    ) -> Result<String, OcrStringError> {
+        self.match_glyphs_with(font, max_distance)
+            .into_iter()
+            .map(|glyph| glyph.map(|m| m.c))
+            .collect()
+    }
+
+    /// Finds the nearest known char for each glyph of the `OcrString`
+    ///
+    /// Glyphs that have more than `max_distance` pixels different to every known char are
+    /// returned as an `OcrStringError::UnknownGlyph`
+    pub fn match_glyphs(&self, max_distance: u32) -> Vec<Result<GlyphMatch, OcrStringError>> {
+        match Font::builtin(self.height) {
+            Some(font) => self.match_glyphs_with(font, max_distance),
+            None => vec![
+                Err(OcrStringError::InvalidHeight {
+                    height: self.height
+                });
+                self.len()
+            ],
         }
-        Some(result.iter().collect())
+    }
+
+    /// Finds the nearest char from the supplied font for each glyph of the `OcrString`
+    pub fn match_glyphs_with(
+        &self,
+        font: &Font,
+        max_distance: u32,
+    ) -> Vec<Result<GlyphMatch, OcrStringError>> {
+        if font.height != self.height {
+            return vec![
+                Err(OcrStringError::InvalidHeight {
+                    height: self.height
+                });
+                self.len()
+            ];
+        }
+        self.grouped
+            .iter()
+            .enumerate()
+            .map(|(index, group)| {
+                font.nearest(&hash_char(group.iter(), self.height))
+                    .filter(|m| m.distance <= max_distance)
+                    .ok_or_else(|| OcrStringError::UnknownGlyph {
+                        index,
+                        art: self.glyph_art(group),
+                    })
+            })
+            .collect()
     }
 
     /// Gets the number of chars that make up this `OcrString`
@@ -165,35 +513,74 @@ impl OcrString {
     pub fn debug_print(&self) {
         println!("OcrString: Font height: {}", self.height);
         for (i, group) in self.grouped.iter().enumerate() {
-            let code = hash_char(group.iter());
+            let code = hash_char(group.iter(), self.height);
             println!(
-                "{}: {} = {}: {} {:?}",
+                "{}: {:?} = {}: {} {:?}",
                 i,
                 code,
-                CHAR_LOOKUP.get(&code).unwrap_or(&'?'),
+                Font::builtin(self.height)
+                    .and_then(|font| font.glyphs.get(&code))
+                    .unwrap_or(&'?'),
                 group.len(),
                 group
             );
-            let mut grid = vec![vec![' '; 6]; self.height];
-            for &(x, y) in group {
-                grid[y][x] = '#';
-            }
-            for row in &grid {
-                println!("{}", row.iter().collect::<String>());
-            }
+            print!("{}", self.glyph_art(group));
+        }
+    }
+
+    /// Draws a glyph as ASCII art using '#' and '.'
+    fn glyph_art(&self, group: &HashSet<Point>) -> String {
+        let width = group.iter().map(|&(x, _)| x + 1).max().unwrap_or(0);
+        let mut grid = vec![vec!['.'; width]; self.height];
+        for &(x, y) in group {
+            grid[y][x] = '#';
         }
+        grid.iter()
+            .map(|row| row.iter().collect::<String>() + "\n")
+            .collect()
     }
 }
 
 impl fmt::Display for OcrString {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.decode().unwrap())
+        match self.decode() {
+            Some(decoded) => write!(f, "{}", decoded),
+            None => write!(f, "{}", "?".repeat(self.len())),
+        }
+    }
+}
+
+/// Finds the rows that hold the chars when stray pixels above or below them make the points
+/// taller than the font, returning (top, height)
+///
+/// Uses the smallest built in font height that has almost all of the points within a single
+/// run of rows of that height
+fn text_rows(points: &[Point], min_y: usize, height: usize) -> Option<(usize, usize)> {
+    let mut rows = vec![0; height];
+    for &(_, y) in points {
+        rows[y - min_y] += 1;
     }
+    FONT_SIZES
+        .iter()
+        .map(|&(text_height, _)| text_height)
+        .filter(|&text_height| text_height <= height)
+        .find_map(|text_height| {
+            let (inside, top) = (0..=height - text_height)
+                .map(|top| (rows[top..top + text_height].iter().sum::<usize>(), top))
+                .max_by_key(|&(inside, top)| (inside, std::cmp::Reverse(top)))?;
+            ((points.len() - inside) * 10 <= inside).then_some((min_y + top, text_height))
+        })
 }
 
-/// Generates a uniq hash value for each char in the alphabet
-fn hash_char<'a>(points: impl Iterator<Item = &'a Point>) -> CharHash {
-    points.fold(0, |hash, (x, y)| hash + ((y + 1) * (x + 1) * (x + (y * 4)))) as CharHash
+/// Generates a uniq hash value for each char by setting a bit for each point in each row
+///
+/// Only the first 64 columns of a char are used
+fn hash_char<'a>(points: impl Iterator<Item = &'a Point>, height: usize) -> CharHash {
+    let mut rows = vec![0; height];
+    for &(x, y) in points {
+        rows[y] |= 1u64.checked_shl(x as u32).unwrap_or(0);
+    }
+    rows
 }
 
 #[cfg(test)]
@@ -238,9 +625,11 @@ mod tests {
         .##..###..#..#...##.####.###...##...##.
         #..#.#..#.#.#.....#.#....#..#.#..#.#..#
         "};
+        let ocr: OcrString = SAMPLE.parse().unwrap();
+        assert_eq!(ocr.decode(), None);
         assert_eq!(
-            SAMPLE.parse::<OcrString>().err(),
-            Some(OcrStringError::InvalidHeight { height: 2 })
+            ocr.try_decode(),
+            Err(OcrStringError::InvalidHeight { height: 2 })
         );
     }
 
@@ -275,6 +664,254 @@ mod tests {
         assert_eq!(ocr.decode(), Some("ABKJFBGC".to_owned()));
     }
 
+    #[test]
+    fn test_grids() {
+        static SAMPLE: &str = indoc! {"
+        .##..###..#..#...##.####.###...##...##.
+        #..#.#..#.#.#.....#.#....#..#.#..#.#..#
+        #..#.###..##......#.###..###..#....#...
+        ####.#..#.#.#.....#.#....#..#.#.##.#...
+        #..#.#..#.#.#..#..#.#....#..#.#..#.#..#
+        #..#.###..#..#..##..#....###...###..##.
+        "};
+        let grid: VecGrid<char> = SAMPLE.parse().unwrap();
+        let ocr = OcrString::from(&grid);
+        assert_eq!(ocr.try_decode(), Ok("ABKJFBGC".to_owned()));
+        // Screen output from Intcode with negative positions
+        let screen: MapGrid<i64> = grid
+            .into_iter()
+            .map(|(pos, &c)| (pos + (-20, -10), if c == '#' { 1 } else { 0 }))
+            .collect();
+        let ocr = OcrString::from(&screen);
+        assert_eq!(ocr.try_decode(), Ok("ABKJFBGC".to_owned()));
+        let ocr = OcrString::from_map_grid(&screen, |&v| v == 0);
+        assert_ne!(ocr.try_decode(), Ok("ABKJFBGC".to_owned()));
+        let ocr: OcrString = screen
+            .iter()
+            .filter(|(_, &v)| v == 1)
+            .map(|(&pos, _)| pos)
+            .collect();
+        assert_eq!(ocr.try_decode(), Ok("ABKJFBGC".to_owned()));
+    }
+
+    #[test]
+    fn test_full_alphabets() {
+        for (chars, dictionary) in ALPHABETS {
+            let ocr: OcrString = dictionary.parse().unwrap();
+            assert_eq!(ocr.try_decode(), Ok(chars.to_owned()));
+        }
+    }
+
+    #[test]
+    fn test_unknown_glyph() {
+        static SAMPLE: &str = indoc! {"
+        .##..#.#.
+        #..#..#.#
+        #..#.#.#.
+        ####..#.#
+        #..#.#.#.
+        #..#..#.#
+        "};
+        let ocr: OcrString = SAMPLE.parse().unwrap();
+        assert_eq!(ocr.decode(), Some("A?".to_owned()));
+        let glyphs = ocr.decode_glyphs();
+        assert_eq!(glyphs[0], Ok('A'));
+        let error = ocr.try_decode().unwrap_err();
+        assert_eq!(
+            error,
+            OcrStringError::UnknownGlyph {
+                index: 1,
+                art: "#.#.\n.#.#\n#.#.\n.#.#\n#.#.\n.#.#\n".to_owned()
+            }
+        );
+        assert!(error.to_string().ends_with("#.#.\n.#.#\n"));
+    }
+
+    #[test]
+    fn test_nearest_with_noise() {
+        // ABKJFBGC with a missing pixel in the A, a stray pixel next to the K and two wrong pixels in the J
+        static SAMPLE: &str = indoc! {"
+        .##..###..#..##...#.####.###...##...##.
+        #..#.#..#.#.#.....#.#....#..#.#..#.#..#
+        #..#.###..##......#.###..###..#....#...
+        #.##.#..#.#.#.....#.#....#..#.#.##.#...
+        #..#.#..#.#.#..#..#.#....#..#.#..#.#..#
+        #..#.###..#..#.###..#....###...###..##.
+        "};
+        let ocr: OcrString = SAMPLE.parse().unwrap();
+        assert_eq!(ocr.decode(), Some("?B??FBGC".to_owned()));
+        assert_eq!(ocr.decode_nearest(2), Ok("ABKJFBGC".to_owned()));
+        assert!(matches!(
+            ocr.decode_nearest(1),
+            Err(OcrStringError::UnknownGlyph { index: 3, .. })
+        ));
+        let matches = ocr.match_glyphs(2);
+        assert_eq!(
+            matches[1],
+            Ok(GlyphMatch {
+                c: 'B',
+                distance: 0,
+                confidence: 1.0
+            })
+        );
+        let a = matches[0].as_ref().unwrap();
+        assert_eq!((a.c, a.distance), ('A', 1));
+        assert!(a.confidence > 0.9 && a.confidence < 1.0);
+    }
+
+    #[test]
+    fn test_stray_pixel_outside_rows() {
+        // ABKJFBGC with a stray pixel above the text and another two rows below it
+        static SAMPLE: &str = indoc! {"
+        ............#..........................
+        .##..###..#..#...##.####.###...##...##.
+        #..#.#..#.#.#.....#.#....#..#.#..#.#..#
+        #..#.###..##......#.###..###..#....#...
+        ####.#..#.#.#.....#.#....#..#.#.##.#...
+        #..#.#..#.#.#..#..#.#....#..#.#..#.#..#
+        #..#.###..#..#..##..#....###...###..##.
+        .......................................
+        ..............................#........
+        "};
+        let ocr: OcrString = SAMPLE.parse().unwrap();
+        assert_eq!(ocr.height, 6);
+        assert_eq!(ocr.len(), 8);
+        assert_eq!(ocr.try_decode(), Ok("ABKJFBGC".to_owned()));
+    }
+
+    #[test]
+    fn test_stray_pixel_in_gap() {
+        static SAMPLE: &str = indoc! {"
+        ..#....#.######...#.......#####.
+        ..#....#.#........#.......#....#
+        ..#....#.#........#.......#....#
+        ..#....#.#........#.......#....#
+        ..######.#####....#.......#####.
+        ..#....#.#......#.#.......#.....
+        ..#....#.#........#.......#.....
+        ..#....#.#........#.......#.....
+        ..#....#.#........#.......#.....
+        ..#....#.######...######..#.....
+        "};
+        let ocr: OcrString = SAMPLE.parse().unwrap();
+        assert_eq!(ocr.len(), 4);
+        assert_eq!(ocr.try_decode(), Ok("HELP".to_owned()));
+    }
+
+    #[test]
+    fn test_runtime_font() {
+        // A 3 high font where the I is narrower than the other chars
+        static DICTIONARY: &str = "###.#.#.#\n#.#.#.#.#\n###..#..#";
+        let font = Font::new("OVI", DICTIONARY).unwrap();
+        assert_eq!(font.height(), 3);
+        let ocr: OcrString = "..#.#..#..###\n..#.#..#..#.#\n...#...#..###"
+            .parse()
+            .unwrap();
+        assert_eq!(ocr.try_decode_with(&font), Ok("VIO".to_owned()));
+        assert_eq!(
+            ocr.try_decode(),
+            Err(OcrStringError::InvalidHeight { height: 3 })
+        );
+        assert_eq!(
+            Font::new("OV", DICTIONARY).err(),
+            Some(OcrStringError::InvalidDictionary {
+                expected: 2,
+                found: 3
+            })
+        );
+        assert_eq!(
+            Font::new("OO", "###.###\n#.#.#.#\n###.###").err(),
+            Some(OcrStringError::DuplicateGlyph {
+                c: 'O',
+                existing: 'O'
+            })
+        );
+        assert!(matches!(
+            Font::from_file("OVI", "missing_font.txt"),
+            Err(OcrStringError::FontFile { .. })
+        ));
+    }
+
+    #[test]
+    fn test_font_from_file() {
+        // Include the process id so that concurrent test runs don't share the file
+        let path = std::env::temp_dir().join(format!(
+            "aoc_utils_ocr_test_font_from_file_{}.txt",
+            std::process::id()
+        ));
+        std::fs::write(&path, include_str!("6x4.txt")).unwrap();
+        let font = Font::from_file("ABCEFGHIJKLOPRSUYZ", &path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let ocr: OcrString = include_str!("6x4.txt").parse().unwrap();
+        assert_eq!(ocr.try_decode_with(&font), ocr.try_decode());
+    }
+
+    #[test]
+    fn test_10x6_irregular_spacing() {
+        // Letters with uneven gaps between them and empty space around them
+        static SAMPLE: &str = indoc! {"
+        ................................
+        ..#....#.######...#.......#####.
+        ..#....#.#........#.......#....#
+        ..#....#.#........#.......#....#
+        ..#....#.#........#.......#....#
+        ..######.#####....#.......#####.
+        ..#....#.#........#.......#.....
+        ..#....#.#........#.......#.....
+        ..#....#.#........#.......#.....
+        ..#....#.#........#.......#.....
+        ..#....#.######...######..#.....
+        ................................
+        "};
+        let ocr: OcrString = SAMPLE.parse().unwrap();
+        assert_eq!(ocr.height, 10);
+        assert_eq!(ocr.try_decode(), Ok("HELP".to_owned()));
+    }
+
+    #[test]
+    fn test_single_span() {
+        // A 5 wide Y touching a K leaves no gaps to work out the spacing from
+        static SAMPLE: &str = indoc! {"
+        #...##..#
+        #...##.#.
+        .#.#.##..
+        ..#..#.#.
+        ..#..#.#.
+        ..#..#..#
+        "};
+        let ocr: OcrString = SAMPLE.parse().unwrap();
+        assert_eq!(ocr.len(), 2);
+        assert_eq!(ocr.try_decode(), Ok("YK".to_owned()));
+    }
+
+    #[test]
+    fn test_single_char() {
+        static SAMPLE: &str = indoc! {"
+        #...#
+        #...#
+        .#.#.
+        ..#..
+        ..#..
+        ..#..
+        "};
+        let ocr: OcrString = SAMPLE.parse().unwrap();
+        assert_eq!(ocr.try_decode(), Ok("Y".to_owned()));
+        static TALL: &str = indoc! {"
+        #....#
+        #....#
+        #....#
+        #....#
+        ######
+        #....#
+        #....#
+        #....#
+        #....#
+        #....#
+        "};
+        let ocr: OcrString = TALL.parse().unwrap();
+        assert_eq!(ocr.try_decode(), Ok("H".to_owned()));
+    }
+
     #[test]
     fn test_10x6_str() {
         static SAMPLE: &str = indoc! {"
@@ -0,0 +1,376 @@
+//! A module for performing graph searches (BFS, Dijkstra and A*)
+//!
+//! Searches work on any node type and take a `successors` closure that returns the
+//! nodes reachable from a given node (along with the cost of moving to them for the
+//! weighted searches). Helpers are provided to build these closures for `VecGrid`
+//! and `MapGrid` using their `neighbours_ex` functions.
+
+use crate::grid::{MapGrid, Pos, VecGrid};
+use std::cmp::{Ordering, Reverse};
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+
+/// Trait for values that can be used as the cost of moving between nodes
+///
+/// `Default` is expected to return the zero cost
+pub trait Cost: Copy + Ord + Add<Output = Self> + Default {}
+
+impl<T: Copy + Ord + Add<Output = T> + Default> Cost for T {}
+
+/// Entry in the priority queue, ordered by estimated total cost
+struct Candidate<N, C> {
+    estimate: C,
+    cost: C,
+    node: N,
+}
+
+impl<N, C: Ord> PartialEq for Candidate<N, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<N, C: Ord> Eq for Candidate<N, C> {}
+
+impl<N, C: Ord> PartialOrd for Candidate<N, C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<N, C: Ord> Ord for Candidate<N, C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Break ties by preferring candidates that are further along
+        Reverse(&self.estimate)
+            .cmp(&Reverse(&other.estimate))
+            .then_with(|| self.cost.cmp(&other.cost))
+    }
+}
+
+/// Rebuilds the path to `target` by following the `parents` of each node back to the start
+///
+/// The start node is identified as being the one without a parent
+pub fn reconstruct_path<N: Clone + Eq + Hash>(parents: &HashMap<N, N>, target: &N) -> Vec<N> {
+    let mut path = vec![target.clone()];
+    let mut current = target;
+    while let Some(parent) = parents.get(current) {
+        path.push(parent.clone());
+        current = parent;
+    }
+    path.reverse();
+    path
+}
+
+/// Performs a breadth first search from `start` until `success` returns true
+///
+/// Returns the path taken (including both the start and the end node)
+pub fn bfs<N, FN, IN, FS>(start: &N, mut successors: FN, mut success: FS) -> Option<Vec<N>>
+where
+    N: Clone + Eq + Hash,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = N>,
+    FS: FnMut(&N) -> bool,
+{
+    if success(start) {
+        return Some(vec![start.clone()]);
+    }
+    let mut parents = HashMap::new();
+    let mut queue = VecDeque::from([start.clone()]);
+    while let Some(node) = queue.pop_front() {
+        for next in successors(&node) {
+            if next == *start || parents.contains_key(&next) {
+                continue; // Already visited
+            }
+            parents.insert(next.clone(), node.clone());
+            if success(&next) {
+                return Some(reconstruct_path(&parents, &next));
+            }
+            queue.push_back(next);
+        }
+    }
+    None
+}
+
+/// Performs a breadth first search from `start` to find the number of steps needed
+/// to reach every reachable node (including 0 for the start node)
+pub fn bfs_distances<N, FN, IN>(start: &N, mut successors: FN) -> HashMap<N, usize>
+where
+    N: Clone + Eq + Hash,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = N>,
+{
+    let mut distances = HashMap::from([(start.clone(), 0)]);
+    let mut queue = VecDeque::from([(start.clone(), 0)]);
+    while let Some((node, distance)) = queue.pop_front() {
+        for next in successors(&node) {
+            if let Entry::Vacant(e) = distances.entry(next.clone()) {
+                e.insert(distance + 1);
+                queue.push_back((next, distance + 1));
+            }
+        }
+    }
+    distances
+}
+
+/// Uses Dijkstra's algorithm to find the lowest cost path from `start` to a node
+/// for which `success` returns true
+///
+/// Returns the path taken (including both the start and the end node) and its total cost
+pub fn dijkstra<N, C, FN, IN, FS>(start: &N, successors: FN, success: FS) -> Option<(Vec<N>, C)>
+where
+    N: Clone + Eq + Hash,
+    C: Cost,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = (N, C)>,
+    FS: FnMut(&N) -> bool,
+{
+    astar(start, successors, |_| C::default(), success)
+}
+
+/// Uses Dijkstra's algorithm to find the lowest cost to reach every reachable node from `start`
+///
+/// Returns a map of node to (parent, total cost) which can be used with `reconstruct_path`
+/// (after mapping to just the parent) to get the path to any node.
+/// The start node is not included.
+pub fn dijkstra_all<N, C, FN, IN>(start: &N, mut successors: FN) -> HashMap<N, (N, C)>
+where
+    N: Clone + Eq + Hash,
+    C: Cost,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = (N, C)>,
+{
+    let mut best: HashMap<N, (N, C)> = HashMap::new();
+    let mut queue = BinaryHeap::from([Candidate {
+        estimate: C::default(),
+        cost: C::default(),
+        node: start.clone(),
+    }]);
+    while let Some(Candidate { cost, node, .. }) = queue.pop() {
+        if node != *start && best.get(&node).is_some_and(|&(_, c)| c < cost) {
+            continue; // Already found a cheaper way here
+        }
+        for (next, step) in successors(&node) {
+            let next_cost = cost + step;
+            if next == *start {
+                continue;
+            }
+            match best.entry(next.clone()) {
+                Entry::Occupied(mut e) if next_cost < e.get().1 => {
+                    e.insert((node.clone(), next_cost));
+                }
+                Entry::Vacant(e) => {
+                    e.insert((node.clone(), next_cost));
+                }
+                Entry::Occupied(_) => continue,
+            }
+            queue.push(Candidate {
+                estimate: next_cost,
+                cost: next_cost,
+                node: next,
+            });
+        }
+    }
+    best
+}
+
+/// Uses the A* algorithm to find the lowest cost path from `start` to a node
+/// for which `success` returns true
+///
+/// `heuristic` must never over estimate the remaining cost to reach the goal
+/// otherwise the path found may not be optimal.
+///
+/// Returns the path taken (including both the start and the end node) and its total cost
+pub fn astar<N, C, FN, IN, FH, FS>(
+    start: &N,
+    mut successors: FN,
+    mut heuristic: FH,
+    mut success: FS,
+) -> Option<(Vec<N>, C)>
+where
+    N: Clone + Eq + Hash,
+    C: Cost,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = (N, C)>,
+    FH: FnMut(&N) -> C,
+    FS: FnMut(&N) -> bool,
+{
+    let mut parents: HashMap<N, N> = HashMap::new();
+    let mut costs: HashMap<N, C> = HashMap::from([(start.clone(), C::default())]);
+    let mut queue = BinaryHeap::from([Candidate {
+        estimate: heuristic(start),
+        cost: C::default(),
+        node: start.clone(),
+    }]);
+    while let Some(Candidate { cost, node, .. }) = queue.pop() {
+        if success(&node) {
+            return Some((reconstruct_path(&parents, &node), cost));
+        }
+        if costs.get(&node).is_some_and(|&c| c < cost) {
+            continue; // Already found a cheaper way here
+        }
+        for (next, step) in successors(&node) {
+            let next_cost = cost + step;
+            match costs.entry(next.clone()) {
+                Entry::Occupied(mut e) if next_cost < *e.get() => {
+                    e.insert(next_cost);
+                }
+                Entry::Vacant(e) => {
+                    e.insert(next_cost);
+                }
+                Entry::Occupied(_) => continue,
+            }
+            parents.insert(next.clone(), node.clone());
+            queue.push(Candidate {
+                estimate: next_cost + heuristic(&next),
+                cost: next_cost,
+                node: next,
+            });
+        }
+    }
+    None
+}
+
+/// Returns a heuristic for use with `astar` that gives the manhattan distance to `target`
+pub fn manhattan_heuristic(target: Pos) -> impl Fn(&Pos) -> usize {
+    move |pos| pos.manhattan_distance(&target)
+}
+
+/// Returns a successors function for searching a `VecGrid` using its 4 neighbours
+///
+/// `cost` is called with the value being moved from and to and should return
+/// the cost of the move, or `None` if it is not possible
+pub fn vec_grid_successors<'a, V, C, F>(
+    grid: &'a VecGrid<V>,
+    cost: F,
+) -> impl Fn(&Pos) -> Vec<(Pos, C)> + 'a
+where
+    V: Clone + Copy,
+    F: Fn(V, V) -> Option<C> + 'a,
+{
+    move |&pos| {
+        let from = grid[pos];
+        grid.neighbours_ex(pos)
+            .filter_map(|(_, next, to)| Some((next, cost(from, to?)?)))
+            .collect()
+    }
+}
+
+/// Returns a successors function for searching a `MapGrid` using its 4 neighbours
+///
+/// `cost` is called with the value being moved from and to and should return
+/// the cost of the move, or `None` if it is not possible.
+/// Positions without a value are never moved to.
+pub fn map_grid_successors<'a, V, C, F>(
+    grid: &'a MapGrid<V>,
+    cost: F,
+) -> impl Fn(&Pos) -> Vec<(Pos, C)> + 'a
+where
+    V: Clone,
+    F: Fn(&V, &V) -> Option<C> + 'a,
+{
+    move |pos| match grid.get(pos) {
+        Some(from) => grid
+            .neighbours_ex(*pos)
+            .filter_map(|(_, next, to)| Some((next, cost(from, to?)?)))
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+/// Converts a weighted successors function into an unweighted one for use with `bfs`
+pub fn unweighted<N, C>(
+    successors: impl Fn(&N) -> Vec<(N, C)>,
+) -> impl Fn(&N) -> std::vec::IntoIter<N> {
+    move |node| {
+        successors(node)
+            .into_iter()
+            .map(|(next, _)| next)
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    static MAZE: &str = indoc! {"
+    #########
+    #S..#...#
+    #.#.#.#.#
+    #.#...#E#
+    #########
+    "};
+
+    fn open(_: char, to: char) -> Option<usize> {
+        (to != '#').then_some(1)
+    }
+
+    #[test]
+    fn test_bfs_vec_grid() {
+        let grid: VecGrid<char> = MAZE.parse().unwrap();
+        let path = bfs(
+            &Pos::new(1, 1),
+            unweighted(vec_grid_successors(&grid, open)),
+            |p| grid[*p] == 'E',
+        )
+        .unwrap();
+        assert_eq!(path.len(), 13);
+        assert_eq!(path.first(), Some(&Pos::new(1, 1)));
+        assert_eq!(path.last(), Some(&Pos::new(7, 3)));
+    }
+
+    #[test]
+    fn test_bfs_distances() {
+        let grid: VecGrid<char> = MAZE.parse().unwrap();
+        let distances = bfs_distances(
+            &Pos::new(1, 1),
+            unweighted(vec_grid_successors(&grid, open)),
+        );
+        assert_eq!(distances[&Pos::new(1, 1)], 0);
+        assert_eq!(distances[&Pos::new(1, 3)], 2);
+        assert_eq!(distances[&Pos::new(7, 3)], 12);
+        assert_eq!(
+            distances.len(),
+            grid.values().filter(|&&c| c != '#').count()
+        );
+    }
+
+    #[test]
+    fn test_weighted_searches_agree() {
+        let grid: VecGrid<u8> = "1163751742\n1381373672\n2136511328\n3694931569\n7463417111"
+            .parse()
+            .unwrap();
+        let start = Pos::new(0, 0);
+        let target = Pos::new(grid.width() - 1, grid.height() - 1);
+        let successors = vec_grid_successors(&grid, |_, to| Some(to as usize));
+        let (path, cost) = dijkstra(&start, &successors, |p| *p == target).unwrap();
+        assert_eq!(cost, path.iter().skip(1).map(|&p| grid[p] as usize).sum());
+        let (_, astar_cost) = astar(&start, &successors, manhattan_heuristic(target), |p| {
+            *p == target
+        })
+        .unwrap();
+        assert_eq!(astar_cost, cost);
+        let all = dijkstra_all(&start, &successors);
+        assert_eq!(all[&target].1, cost);
+        let parents = all.iter().map(|(n, (p, _))| (*n, *p)).collect();
+        assert_eq!(reconstruct_path(&parents, &target).len(), path.len());
+    }
+
+    #[test]
+    fn test_map_grid_unreachable() {
+        let grid: MapGrid<char> = "S.#.E".parse().unwrap();
+        let successors = map_grid_successors(&grid, |_, &to| (to != '#').then_some(1usize));
+        assert_eq!(
+            dijkstra(&Pos::new(0, 0), &successors, |p| *p == Pos::new(4, 0)),
+            None
+        );
+        assert_eq!(
+            dijkstra(&Pos::new(0, 0), &successors, |p| *p == Pos::new(1, 0)),
+            Some((vec![Pos::new(0, 0), Pos::new(1, 0)], 1))
+        );
+    }
+}